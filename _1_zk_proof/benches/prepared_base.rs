@@ -0,0 +1,134 @@
+//! Multiplications by a base point other than G (here a Pedersen `H`): variable-base `ProjectivePoint` vs `PreparedBase`
+use _1_zk_proof::prepared_base::{BasePoint, PreparedBase};
+use _1_zk_proof::range_proof::PedersenGens;
+use _1_zk_proof::sigma_proof::random_scalar;
+use _1_zk_proof::{DLogProof, GENERATOR};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::{ProjectivePoint, Scalar};
+use rand::thread_rng;
+
+_1_zk_proof::define_proof! {
+    pedersen_opening, "pedersen-opening", (x, r), (C), (G, H) : C = (x * G + r * H)
+}
+
+const BATCH_SIZE: u64 = 64;
+
+fn pedersen_h() -> ProjectivePoint {
+    PedersenGens::default().b_blinding
+}
+
+fn scalar_mul(c: &mut Criterion) {
+    let h = pedersen_h();
+    let prepared_h = PreparedBase::new(h);
+    let mut group = c.benchmark_group("scalar multiplication");
+    group.bench_function("ProjectivePoint H", |b| {
+        b.iter_batched(
+            || random_scalar(&mut thread_rng()),
+            |k| h * k,
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("PreparedBase H", |b| {
+        b.iter_batched(
+            || random_scalar(&mut thread_rng()),
+            |k| prepared_h.mul_scalar(&k),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("generator G (k256 table, for reference)", |b| {
+        b.iter_batched(
+            || random_scalar(&mut thread_rng()),
+            |k| ProjectivePoint::mul_by_generator(&k),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+
+    c.bench_function("PreparedBase::new", |b| b.iter(|| PreparedBase::new(h)));
+}
+
+fn dlog_proof(c: &mut Criterion) {
+    let h = pedersen_h();
+    let prepared_h = PreparedBase::new(h);
+    let x = random_scalar(&mut thread_rng());
+    let y = h * x;
+    let proof = DLogProof::prove("sid", 1, x, y, h);
+
+    let mut group = c.benchmark_group("DLogProof with base point H");
+    group.bench_function("prove, ProjectivePoint", |b| {
+        b.iter(|| DLogProof::prove("sid", 1, x, y, h))
+    });
+    group.bench_function("prove, PreparedBase", |b| {
+        b.iter(|| DLogProof::prove("sid", 1, x, y, &prepared_h))
+    });
+    group.bench_function("verify, ProjectivePoint", |b| {
+        b.iter(|| proof.verify("sid", 1, y, h))
+    });
+    group.bench_function("verify, PreparedBase", |b| {
+        b.iter(|| proof.verify("sid", 1, y, &prepared_h))
+    });
+    group.finish();
+}
+
+fn batch_verify(c: &mut Criterion) {
+    let h = pedersen_h();
+    let prepared_h = PreparedBase::new(h);
+    let pids: Vec<u64> = (0..BATCH_SIZE).collect();
+    let (proofs, commitments): (Vec<_>, Vec<_>) = pids
+        .iter()
+        .map(|pid| {
+            let (x, r): (Scalar, Scalar) = (
+                random_scalar(&mut thread_rng()),
+                random_scalar(&mut thread_rng()),
+            );
+            let commitment = GENERATOR * x + h * r;
+            let assignments = pedersen_opening::ProveAssignments {
+                x: &x,
+                r: &r,
+                C: &commitment,
+                G: &GENERATOR,
+                H: &h,
+            };
+            (
+                pedersen_opening::prove("sid", *pid, assignments),
+                commitment,
+            )
+        })
+        .unzip();
+
+    let mut group = c.benchmark_group("batch verify 64 Pedersen openings");
+    for (name, h) in [
+        ("ProjectivePoint", &h as &dyn BasePoint),
+        ("PreparedBase", &prepared_h),
+    ] {
+        let assignments = pedersen_opening::BatchVerifyAssignments {
+            C: commitments.clone(),
+            G: &GENERATOR,
+            H: h,
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| pedersen_opening::batch_verify(&proofs, "sid", &pids, assignments.clone()))
+        });
+        group.bench_function(format!("{name}, one by one"), |b| {
+            b.iter(|| {
+                proofs
+                    .iter()
+                    .zip(&pids)
+                    .zip(&commitments)
+                    .all(|((proof, pid), commitment)| {
+                        let assignments = pedersen_opening::VerifyAssignments {
+                            C: commitment,
+                            G: &GENERATOR,
+                            H: h,
+                        };
+                        pedersen_opening::verify(proof, "sid", *pid, assignments)
+                    })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scalar_mul, dlog_proof, batch_verify);
+criterion_main!(benches);
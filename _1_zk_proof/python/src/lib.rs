@@ -0,0 +1,184 @@
+//! Python bindings for the DLOG proof of `_1_zk_proof`, with the interface of `DLogProof` from `dlog_proof.py`
+//!
+//! Its proofs are not interoperable with the Python implementation: the challenge hashes the raw sid bytes, the little-endian pid
+//! and compressed SEC1 points, where `dlog_proof.py` hashes the encodings of the `htss_ecdsa` serializers,
+//! and `to_dict` holds the hex of the compressed point and of the scalar instead of the serializers' output.
+//!
+//! Points are accepted as SEC1-encoded `bytes`, or as any point object with `x()` and `y()` methods (e.g. `ecdsa.ellipticcurve.PointJacobi`),
+//! and returned as `ecdsa.ellipticcurve.PointJacobi`, like the reference. Scalars are Python `int`s.
+use _1_zk_proof::{encoding, DLogProof, GENERATOR};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, ProjectivePoint, PublicKey, Scalar};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyInt};
+
+/// Python int from big-endian bytes
+fn int_from_bytes<'py>(py: Python<'py>, bytes: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    py.get_type::<PyInt>()
+        .call_method1("from_bytes", (PyBytes::new(py, bytes), "big"))
+}
+
+/// 32-byte big-endian encoding of a non-negative Python int < 2^256
+fn int_to_bytes(int: &Bound<'_, PyAny>) -> PyResult<[u8; 32]> {
+    let bytes = int.call_method1("to_bytes", (32, "big"))?;
+    let bytes = bytes.downcast::<PyBytes>()?.as_bytes();
+    Ok(bytes.try_into().expect("to_bytes(32) returns 32 bytes"))
+}
+
+/// The curve order q, as a Python int
+fn order(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    int_from_bytes(py, &(-Scalar::ONE).to_bytes())?.add(1)
+}
+
+/// Any Python int, reduced mod q (like the `% q` of the reference implementation)
+fn scalar_from_py(x: &Bound<'_, PyAny>) -> PyResult<Scalar> {
+    let reduced = x.rem(order(x.py())?)?;
+    let bytes = int_to_bytes(&reduced)?;
+    Ok(Option::from(Scalar::from_repr(FieldBytes::from(bytes))).expect("reduced mod q"))
+}
+
+fn scalar_to_py<'py>(py: Python<'py>, s: &Scalar) -> PyResult<Bound<'py, PyAny>> {
+    int_from_bytes(py, &s.to_bytes())
+}
+
+fn point_from_py(name: &str, point: &Bound<'_, PyAny>) -> PyResult<ProjectivePoint> {
+    let sec1 = if let Ok(bytes) = point.downcast::<PyBytes>() {
+        bytes.as_bytes().to_vec()
+    } else if point.hasattr("x")? && point.hasattr("y")? {
+        let x = int_to_bytes(&point.call_method0("x")?)?;
+        let y = int_to_bytes(&point.call_method0("y")?)?;
+        [&[0x04], &x[..], &y[..]].concat()
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "{name} must be SEC1-encoded bytes, or a point with x() and y() methods"
+        )));
+    };
+    PublicKey::from_sec1_bytes(&sec1)
+        .map(|key| key.to_projective())
+        .map_err(|_| PyValueError::new_err(format!("{name} is not a valid secp256k1 point")))
+}
+
+/// `ecdsa.SECP256k1`, the curve of the reference
+fn ecdsa_curve(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    py.import("ecdsa")?.getattr("SECP256k1")
+}
+
+/// An `ecdsa` point, supporting the point arithmetic of the reference
+fn point_to_py<'py>(py: Python<'py>, point: &ProjectivePoint) -> PyResult<Bound<'py, PyAny>> {
+    let curve = ecdsa_curve(py)?;
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("order", curve.getattr("order")?)?;
+    py.import("ecdsa.ellipticcurve")?
+        .getattr("PointJacobi")?
+        .call_method(
+            "from_bytes",
+            (curve.getattr("curve")?, PyBytes::new(py, &point.to_bytes())),
+            Some(&kwargs),
+        )
+}
+
+fn base_point_from_py(base_point: Option<&Bound<'_, PyAny>>) -> PyResult<ProjectivePoint> {
+    base_point.map_or(Ok(GENERATOR), |point| point_from_py("base_point", point))
+}
+
+/// Non-interactive Schnorr ZK DLOG Proof scheme with a Fiat-Shamir transformation
+#[pyclass(name = "DLogProof", module = "zk_proof", eq, frozen)]
+#[derive(Clone, PartialEq)]
+pub struct PyDLogProof(DLogProof);
+
+#[pymethods]
+impl PyDLogProof {
+    #[new]
+    fn new(t: &Bound<'_, PyAny>, s: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let t = point_from_py("t", t)?;
+        let s = scalar_from_py(s)?;
+        Ok(Self(DLogProof::from_parts(t, s)))
+    }
+
+    /// Generator G of secp256k1
+    #[classattr]
+    #[pyo3(name = "G")]
+    fn generator(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+        ecdsa_curve(py)?.getattr("generator")
+    }
+
+    /// Order q of secp256k1
+    #[classattr]
+    fn q(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+        order(py)
+    }
+
+    #[getter]
+    fn t<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        point_to_py(py, self.0.t())
+    }
+
+    #[getter]
+    fn s<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        scalar_to_py(py, self.0.s())
+    }
+
+    /// y = x*base_point
+    #[staticmethod]
+    #[pyo3(signature = (sid, pid, x, y, base_point=None))]
+    fn prove(
+        sid: &str,
+        pid: u64,
+        x: &Bound<'_, PyAny>,
+        y: &Bound<'_, PyAny>,
+        base_point: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let x = scalar_from_py(x)?;
+        let y = point_from_py("y", y)?;
+        let base_point = base_point_from_py(base_point)?;
+        Ok(Self(DLogProof::prove(sid, pid, x, y, base_point)))
+    }
+
+    #[pyo3(signature = (sid, pid, y, base_point=None))]
+    fn verify(
+        &self,
+        sid: &str,
+        pid: u64,
+        y: &Bound<'_, PyAny>,
+        base_point: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        let y = point_from_py("y", y)?;
+        let base_point = base_point_from_py(base_point)?;
+        Ok(self.0.verify(sid, pid, y, base_point))
+    }
+
+    /// `{"t": hex of the compressed point, "s": hex of the 32-byte scalar}`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("t", encoding::point_to_hex(self.0.t()))?;
+        dict.set_item("s", encoding::scalar_to_hex(self.0.s()))?;
+        Ok(dict)
+    }
+
+    fn to_str(&self, py: Python<'_>) -> PyResult<String> {
+        py.import("json")?
+            .call_method1("dumps", (self.to_dict(py)?,))?
+            .extract()
+    }
+
+    #[staticmethod]
+    fn from_dict(data: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let invalid = || PyValueError::new_err("not a serialized DLogProof");
+        let t: String = data.get_item("t")?.extract()?;
+        let s: String = data.get_item("s")?.extract()?;
+        let t = encoding::point_from_hex(&t).ok_or_else(invalid)?;
+        let s = encoding::scalar_from_hex(&s).ok_or_else(invalid)?;
+        Ok(Self(DLogProof::from_parts(t, s)))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("DLogProof({})", self.to_str(py)?))
+    }
+}
+
+#[pymodule]
+fn zk_proof(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDLogProof>()
+}
@@ -0,0 +1,22 @@
+"""Loads the reference implementation `_1_zk_proof/dlog_proof.py`
+
+The reference imports its serializers from the private `htss_ecdsa` package:
+the tests running it are skipped when it isn't installed.
+"""
+
+import importlib.util
+from pathlib import Path
+
+import pytest
+
+REFERENCE_PATH = Path(__file__).parents[2] / "dlog_proof.py"
+
+
+@pytest.fixture(scope="session")
+def reference():
+    """The `dlog_proof` module of the reference implementation"""
+    pytest.importorskip("htss_ecdsa.common.serializers", reason="the reference needs the private htss_ecdsa package")
+    spec = importlib.util.spec_from_file_location("dlog_proof", REFERENCE_PATH)
+    module = importlib.util.module_from_spec(spec)
+    spec.loader.exec_module(module)
+    return module
@@ -0,0 +1,234 @@
+use crate::schnorr::{self, SchnorrSignature};
+use crate::{DLogProof, GENERATOR};
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Schnorr adaptor signatures, for atomic swaps
+///
+/// A pre-signature is a Schnorr signature "locked" with an adaptor point Y = yG:
+/// it becomes a valid signature only once adapted with y, and anyone seeing both the pre-signature and the final signature learns y.
+/// Protocol (signer key pair x, X = xG):
+/// 1. Pre-sign: the signer generates a random r, computes R = rG, c = H(R + Y, X, m) and s' = r + c * x. The pre-signature is (R, s')
+/// 2. Pre-verify: s' * G == R + c * X
+/// 3. Adapt: whoever knows y computes s = s' + y. (R + Y, s) is a standard Schnorr signature: sG == R + cX + yG == (R + Y) + cX
+/// 4. Extract: from the pre-signature and the published signature, y = s - s'
+///
+/// In a swap, both parties pre-sign their side with the same adaptor point:
+/// publishing the adapted signature to claim one side reveals y to the other party, who can then adapt the pre-signature of the other side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreSignature {
+    #[serde(with = "crate::encoding::serde_point")]
+    r: ProjectivePoint,
+    s: Scalar,
+}
+impl PreSignature {
+    /// Pre-sign `msg` with secret key x, locked by the adaptor point Y
+    #[cfg(feature = "std")]
+    pub fn sign(
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        x: Scalar,
+        adaptor_point: &ProjectivePoint,
+    ) -> Self {
+        Self::sign_with_rng(sid, pid, msg, x, adaptor_point, &mut rand::thread_rng())
+    }
+
+    pub fn sign_with_rng(
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        x: Scalar,
+        adaptor_point: &ProjectivePoint,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let r = Scalar::generate_vartime(rng);
+        let big_r = ProjectivePoint::mul_by_generator(&r);
+        let public_key = ProjectivePoint::mul_by_generator(&x);
+        let c = schnorr::challenge(sid, pid, &(big_r + adaptor_point), &public_key, msg);
+        Self {
+            r: big_r,
+            s: r + c * x,
+        }
+    }
+
+    /// Check that the pre-signature becomes a valid signature of `msg` by `public_key` once adapted with the discrete log of Y
+    pub fn verify(
+        &self,
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        public_key: &ProjectivePoint,
+        adaptor_point: &ProjectivePoint,
+    ) -> bool {
+        let c = schnorr::challenge(sid, pid, &(self.r + adaptor_point), public_key, msg);
+        ProjectivePoint::mul_by_generator(&self.s) == self.r + public_key * &c
+    }
+
+    /// Complete the pre-signature with the adaptor secret y, into a Schnorr signature
+    pub fn adapt(&self, adaptor_secret: &Scalar) -> SchnorrSignature {
+        SchnorrSignature {
+            r: self.r + ProjectivePoint::mul_by_generator(adaptor_secret),
+            s: self.s + adaptor_secret,
+        }
+    }
+
+    /// Recover the adaptor secret y from a signature adapted from this pre-signature
+    ///
+    /// Returns None if the signature wasn't adapted from this pre-signature with the discrete log of `adaptor_point`
+    pub fn extract(
+        &self,
+        signature: &SchnorrSignature,
+        adaptor_point: &ProjectivePoint,
+    ) -> Option<Scalar> {
+        let y = signature.s() - &self.s;
+        let is_adapted = *signature.r() == self.r + adaptor_point
+            && ProjectivePoint::mul_by_generator(&y) == *adaptor_point;
+        is_adapted.then_some(y)
+    }
+}
+
+/// An adaptor point Y = yG, with a `DLogProof` that its creator knows y
+///
+/// Without the proof, a malicious party could hand out an adaptor point nobody can adapt, locking the other side's funds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptorPoint {
+    point: ProjectivePoint,
+    proof: DLogProof,
+}
+impl AdaptorPoint {
+    /// Generate a random adaptor secret y, and the matching adaptor point
+    #[cfg(feature = "std")]
+    pub fn random(sid: &str, pid: u64) -> (Scalar, Self) {
+        Self::random_with_rng(sid, pid, &mut rand::thread_rng())
+    }
+
+    pub fn random_with_rng(
+        sid: &str,
+        pid: u64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Scalar, Self) {
+        let y = Scalar::generate_vartime(rng);
+        (y, Self::from_secret_with_rng(sid, pid, y, rng))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_secret(sid: &str, pid: u64, y: Scalar) -> Self {
+        Self::from_secret_with_rng(sid, pid, y, &mut rand::thread_rng())
+    }
+
+    pub fn from_secret_with_rng(
+        sid: &str,
+        pid: u64,
+        y: Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let point = ProjectivePoint::mul_by_generator(&y);
+        let proof = DLogProof::prove_with_rng(sid, pid, y, point, GENERATOR, rng);
+        Self { point, proof }
+    }
+
+    /// Check the proof that the adaptor point is well-formed (its creator knows its discrete log)
+    pub fn verify(&self, sid: &str, pid: u64) -> bool {
+        self.point != ProjectivePoint::IDENTITY
+            && self.proof.verify(sid, pid, self.point, GENERATOR)
+    }
+
+    pub fn point(&self) -> &ProjectivePoint {
+        &self.point
+    }
+
+    pub fn proof(&self) -> &DLogProof {
+        &self.proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    fn keypair() -> (Scalar, ProjectivePoint) {
+        let x = Scalar::generate_vartime(&mut thread_rng());
+        (x, ProjectivePoint::mul_by_generator(&x))
+    }
+
+    #[test]
+    fn adapted_pre_signature_is_valid_signature() {
+        let (x, public_key) = keypair();
+        let (y, adaptor) = AdaptorPoint::random("sid", 1);
+
+        let pre_sig = PreSignature::sign("sid", 1, b"msg", x, adaptor.point());
+        assert!(pre_sig.verify("sid", 1, b"msg", &public_key, adaptor.point()));
+        // a pre-signature is not a signature
+        let not_adapted = SchnorrSignature {
+            r: pre_sig.r,
+            s: pre_sig.s,
+        };
+        assert!(!not_adapted.verify("sid", 1, b"msg", &public_key));
+
+        let sig = pre_sig.adapt(&y);
+        assert!(sig.verify("sid", 1, b"msg", &public_key));
+        assert_eq!(pre_sig.extract(&sig, adaptor.point()), Some(y));
+    }
+
+    #[test]
+    fn pre_signature_invalid_for_other_adaptor_point() {
+        let (x, public_key) = keypair();
+        let (_, adaptor) = AdaptorPoint::random("sid", 1);
+        let (other_y, other_adaptor) = AdaptorPoint::random("sid", 1);
+
+        let pre_sig = PreSignature::sign("sid", 1, b"msg", x, adaptor.point());
+        assert!(!pre_sig.verify("sid", 1, b"msg", &public_key, other_adaptor.point()));
+        assert!(!pre_sig
+            .adapt(&other_y)
+            .verify("sid", 1, b"msg", &public_key));
+        assert_eq!(
+            pre_sig.extract(&pre_sig.adapt(&other_y), adaptor.point()),
+            None
+        );
+    }
+
+    #[test]
+    fn adaptor_point_proof() {
+        let (_, adaptor) = AdaptorPoint::random("sid", 1);
+        assert!(adaptor.verify("sid", 1));
+        assert!(!adaptor.verify("sid", 2));
+
+        let (_, other) = AdaptorPoint::random("sid", 1);
+        let forged = AdaptorPoint {
+            point: adaptor.point,
+            proof: other.proof,
+        };
+        assert!(!forged.verify("sid", 1));
+    }
+
+    #[test]
+    fn atomic_swap() {
+        let (alice_x, alice_pk) = keypair();
+        let (bob_x, bob_pk) = keypair();
+        let tx_to_alice: &[u8] = b"bob pays 1 BTC to alice";
+        let tx_to_bob: &[u8] = b"alice pays 20 ETH to bob";
+
+        // Alice picks the adaptor secret and proves the adaptor point is well-formed
+        let (y, adaptor) = AdaptorPoint::random("swap", 0);
+        assert!(adaptor.verify("swap", 0));
+
+        // both pre-sign their payment, locked by the same adaptor point, and check each other's pre-signature
+        let bob_pre_sig = PreSignature::sign("swap", 0, tx_to_alice, bob_x, adaptor.point());
+        let alice_pre_sig = PreSignature::sign("swap", 0, tx_to_bob, alice_x, adaptor.point());
+        assert!(bob_pre_sig.verify("swap", 0, tx_to_alice, &bob_pk, adaptor.point()));
+        assert!(alice_pre_sig.verify("swap", 0, tx_to_bob, &alice_pk, adaptor.point()));
+
+        // Alice claims her payment by publishing Bob's adapted signature...
+        let bob_sig = bob_pre_sig.adapt(&y);
+        assert!(bob_sig.verify("swap", 0, tx_to_alice, &bob_pk));
+
+        // ...which reveals y to Bob, who can then claim his
+        let extracted_y = bob_pre_sig.extract(&bob_sig, adaptor.point()).unwrap();
+        let alice_sig = alice_pre_sig.adapt(&extracted_y);
+        assert!(alice_sig.verify("swap", 0, tx_to_bob, &alice_pk));
+    }
+}
@@ -0,0 +1,818 @@
+//! BIP-32 hierarchical deterministic keys on secp256k1
+//!
+//! Every extended key is a key pair (k, K = kG) plus a 32-byte chain code c. Child i of a parent (k, K, c):
+//! 1. (IL, IR) = HMAC-SHA512(c, 0x00 || k || i) for a hardened index i >= 2^31, HMAC-SHA512(c, K || i) otherwise
+//! 2. the child key is k_i = IL + k (K_i = IL*G + K), and its chain code is IR
+//!
+//! Non-hardened children can be derived from the extended public key (K, c) alone, hardened ones need the parent private key.
+//! The master key comes from a seed: (IL, IR) = HMAC-SHA512("Bitcoin seed", seed), with key IL and chain code IR.
+//! See <https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki>
+use crate::encoding::{self, POINT_LEN};
+use crate::range_proof::PedersenGens;
+use crate::GENERATOR;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::elliptic_curve::zeroize::Zeroize;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+/// Index of the first hardened child
+pub const HARDENED: u32 = 1 << 31;
+
+const MASTER_HMAC_KEY: &[u8] = b"Bitcoin seed";
+/// Version bytes of the Base58Check serialization, on mainnet
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+/// version || depth || parent fingerprint || child number || chain code || key
+const SERIALIZED_LEN: usize = 4 + 1 + 4 + 4 + 32 + POINT_LEN;
+
+type ChainCode = [u8; 32];
+type Fingerprint = [u8; 4];
+
+/// (IL, IR) = HMAC-SHA512(key, data)
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], ChainCode) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    for chunk in data {
+        mac.update(chunk);
+    }
+    let mut output = mac.finalize().into_bytes();
+    let mut il = [0u8; 32];
+    let mut ir = [0u8; 32];
+    il.copy_from_slice(&output[..32]);
+    ir.copy_from_slice(&output[32..]);
+    output.zeroize();
+    (il, ir)
+}
+
+/// IL as a scalar, or an error in the (probability < 2^-127) case where IL >= q
+fn parse_il(mut il: [u8; 32]) -> Result<Scalar, Bip32Error> {
+    let scalar = encoding::scalar_from_bytes(&il);
+    il.zeroize();
+    scalar.ok_or(Bip32Error::InvalidChildKey)
+}
+
+/// First 4 bytes of HASH160(K) = RIPEMD160(SHA256(K))
+fn fingerprint(public_key: &ProjectivePoint) -> Fingerprint {
+    let hash = Ripemd160::digest(Sha256::digest(public_key.to_bytes()));
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&hash[..4]);
+    fingerprint
+}
+
+/// Position of an extended key in the tree, serialized along with the key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KeyInfo {
+    depth: u8,
+    parent_fingerprint: Fingerprint,
+    child_number: u32,
+}
+impl KeyInfo {
+    const MASTER: Self = Self {
+        depth: 0,
+        parent_fingerprint: [0; 4],
+        child_number: 0,
+    };
+
+    fn child(&self, parent_public_key: &ProjectivePoint, index: u32) -> Result<Self, Bip32Error> {
+        Ok(Self {
+            depth: self.depth.checked_add(1).ok_or(Bip32Error::MaxDepth)?,
+            parent_fingerprint: fingerprint(parent_public_key),
+            child_number: index,
+        })
+    }
+}
+
+/// Base58Check serialization of an extended key
+fn to_base58(version: [u8; 4], info: &KeyInfo, chain_code: &ChainCode, key: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(SERIALIZED_LEN);
+    bytes.extend_from_slice(&version);
+    bytes.push(info.depth);
+    bytes.extend_from_slice(&info.parent_fingerprint);
+    bytes.extend_from_slice(&info.child_number.to_be_bytes());
+    bytes.extend_from_slice(chain_code);
+    bytes.extend_from_slice(key);
+    let encoded = bs58::encode(&bytes).with_check().into_string();
+    bytes.zeroize();
+    encoded
+}
+
+/// Decode a Base58Check extended key with the expected version: its position, chain code and key bytes
+fn from_base58(
+    encoded: &str,
+    version: [u8; 4],
+) -> Result<(KeyInfo, ChainCode, [u8; POINT_LEN]), Bip32Error> {
+    let mut bytes = bs58::decode(encoded)
+        .with_check(None)
+        .into_vec()
+        .map_err(|_| Bip32Error::InvalidEncoding)?;
+    if bytes.len() != SERIALIZED_LEN || bytes[..4] != version {
+        bytes.zeroize();
+        return Err(Bip32Error::InvalidEncoding);
+    }
+    let info = KeyInfo {
+        depth: bytes[4],
+        parent_fingerprint: bytes[5..9].try_into().expect("4 bytes"),
+        child_number: u32::from_be_bytes(bytes[9..13].try_into().expect("4 bytes")),
+    };
+    let chain_code = bytes[13..45].try_into().expect("32 bytes");
+    let key = bytes[45..].try_into().expect("33 bytes");
+    bytes.zeroize();
+    // a master key has no parent
+    if info.depth == 0 && (info.parent_fingerprint != [0; 4] || info.child_number != 0) {
+        return Err(Bip32Error::InvalidEncoding);
+    }
+    Ok((info, chain_code, key))
+}
+
+/// Extended private key (k, c): derives both hardened and non-hardened children
+///
+/// The key and chain code are wiped from memory when dropped, and not printed by `Debug`
+#[derive(Clone)]
+pub struct ExtendedPrivateKey {
+    key: Scalar,
+    chain_code: ChainCode,
+    info: KeyInfo,
+}
+impl ExtendedPrivateKey {
+    /// Master key of the tree generated by `seed`, of 16 to 64 bytes
+    pub fn new_master(seed: &[u8]) -> Result<Self, Bip32Error> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(Bip32Error::InvalidSeedLength);
+        }
+        let (il, chain_code) = hmac_sha512(MASTER_HMAC_KEY, &[seed]);
+        let key = parse_il(il)?;
+        if key == Scalar::ZERO {
+            return Err(Bip32Error::InvalidChildKey);
+        }
+        Ok(Self {
+            key,
+            chain_code,
+            info: KeyInfo::MASTER,
+        })
+    }
+
+    /// Master key generated by a random 32-byte seed
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut rand::thread_rng())
+    }
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        loop {
+            let mut seed = [0u8; 32];
+            rng.fill_bytes(&mut seed);
+            let master = Self::new_master(&seed);
+            seed.zeroize();
+            if let Ok(master) = master {
+                return master;
+            }
+        }
+    }
+
+    /// Tweak IL and chain code IR of child `index`: the child key is IL + k
+    fn child_tweak(&self, index: u32) -> Result<(Scalar, ChainCode), Bip32Error> {
+        let (il, chain_code) = if index >= HARDENED {
+            let mut key_bytes = self.key.to_bytes();
+            let output = hmac_sha512(&self.chain_code, &[&[0], &key_bytes, &index.to_be_bytes()]);
+            key_bytes.zeroize();
+            output
+        } else {
+            hmac_sha512(
+                &self.chain_code,
+                &[&self.public_key_point().to_bytes(), &index.to_be_bytes()],
+            )
+        };
+        Ok((parse_il(il)?, chain_code))
+    }
+
+    /// CKDpriv: child `index`, hardened if `index >= HARDENED`
+    ///
+    /// Fails with [`Bip32Error::InvalidChildKey`] in the (probability < 2^-127) case where the child key is invalid: proceed with the next index
+    pub fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        let info = self.info.child(&self.public_key_point(), index)?;
+        let (tweak, chain_code) = self.child_tweak(index)?;
+        let key = tweak + self.key;
+        if key == Scalar::ZERO {
+            return Err(Bip32Error::InvalidChildKey);
+        }
+        Ok(Self {
+            key,
+            chain_code,
+            info,
+        })
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Bip32Error> {
+        path.0
+            .iter()
+            .try_fold(self.clone(), |key, &index| key.derive_child(index))
+    }
+
+    /// N((k, c)) = (kG, c)
+    pub fn public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            key: self.public_key_point(),
+            chain_code: self.chain_code,
+            info: self.info,
+        }
+    }
+
+    fn public_key_point(&self) -> ProjectivePoint {
+        ProjectivePoint::mul_by_generator(&self.key)
+    }
+
+    pub fn expose_secret(&self) -> &Scalar {
+        &self.key
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.info.depth
+    }
+
+    pub fn child_number(&self) -> u32 {
+        self.info.child_number
+    }
+
+    /// Pedersen commitment C = k*G + r*H to the private key, for a random blinding r: the parent commitment of [`ChildKeyProof`]
+    pub fn commit(&self, blinding: &Scalar) -> ProjectivePoint {
+        PedersenGens::default().commit(self.key, *blinding)
+    }
+
+    /// `xprv...` Base58Check serialization
+    pub fn to_base58(&self) -> String {
+        let mut key = [0u8; POINT_LEN];
+        key[1..].copy_from_slice(&self.key.to_bytes());
+        let encoded = to_base58(XPRV_VERSION, &self.info, &self.chain_code, &key);
+        key.zeroize();
+        encoded
+    }
+
+    pub fn from_base58(encoded: &str) -> Result<Self, Bip32Error> {
+        let (info, chain_code, mut key_bytes) = from_base58(encoded, XPRV_VERSION)?;
+        let key = (key_bytes[0] == 0)
+            .then(|| encoding::scalar_from_bytes(&key_bytes[1..]))
+            .flatten()
+            .filter(|key| *key != Scalar::ZERO);
+        key_bytes.zeroize();
+        Ok(Self {
+            key: key.ok_or(Bip32Error::InvalidEncoding)?,
+            chain_code,
+            info,
+        })
+    }
+}
+impl Drop for ExtendedPrivateKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+impl fmt::Debug for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedPrivateKey")
+            .field("depth", &self.info.depth)
+            .field("child_number", &self.info.child_number)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extended public key (K, c): derives the non-hardened children's public keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    key: ProjectivePoint,
+    chain_code: ChainCode,
+    info: KeyInfo,
+}
+impl ExtendedPublicKey {
+    /// CKDpub: public key of the non-hardened child `index`
+    pub fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        if index >= HARDENED {
+            return Err(Bip32Error::HardenedFromPublicKey);
+        }
+        let info = self.info.child(&self.key, index)?;
+        let (il, chain_code) = hmac_sha512(
+            &self.chain_code,
+            &[&self.key.to_bytes(), &index.to_be_bytes()],
+        );
+        let key = ProjectivePoint::mul_by_generator(&parse_il(il)?) + self.key;
+        if key == ProjectivePoint::IDENTITY {
+            return Err(Bip32Error::InvalidChildKey);
+        }
+        Ok(Self {
+            key,
+            chain_code,
+            info,
+        })
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Bip32Error> {
+        path.0
+            .iter()
+            .try_fold(*self, |key, &index| key.derive_child(index))
+    }
+
+    pub fn public_key(&self) -> &ProjectivePoint {
+        &self.key
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.info.depth
+    }
+
+    pub fn child_number(&self) -> u32 {
+        self.info.child_number
+    }
+
+    /// Identifies the key as the parent of its children: first 4 bytes of HASH160(K)
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.key)
+    }
+
+    /// `xpub...` Base58Check serialization
+    pub fn to_base58(&self) -> String {
+        to_base58(
+            XPUB_VERSION,
+            &self.info,
+            &self.chain_code,
+            &self.key.to_bytes(),
+        )
+    }
+
+    pub fn from_base58(encoded: &str) -> Result<Self, Bip32Error> {
+        let (info, chain_code, key) = from_base58(encoded, XPUB_VERSION)?;
+        Ok(Self {
+            key: encoding::point_from_bytes(&key).ok_or(Bip32Error::InvalidEncoding)?,
+            chain_code,
+            info,
+        })
+    }
+}
+
+/// Path from a key to one of its descendants, e.g. `m/44'/0'/0'/0/1`: hardened indices are marked by `'` or `h`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+impl DerivationPath {
+    pub fn new(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+impl FromStr for DerivationPath {
+    type Err = Bip32Error;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut components = path.split('/');
+        if components.next() != Some("m") {
+            return Err(Bip32Error::InvalidPath);
+        }
+        components
+            .map(|component| {
+                let (number, offset) = match component.strip_suffix(['\'', 'h']) {
+                    Some(number) => (number, HARDENED),
+                    None => (component, 0),
+                };
+                // digits only: no sign, no whitespace
+                if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(Bip32Error::InvalidPath);
+                }
+                match number.parse::<u32>() {
+                    Ok(index) if index < HARDENED => Ok(index + offset),
+                    _ => Err(Bip32Error::InvalidPath),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.0 {
+            match index.checked_sub(HARDENED) {
+                Some(hardened) => write!(f, "/{hardened}'")?,
+                None => write!(f, "/{index}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+crate::define_proof! {
+    /// Knowledge of the opening (k, r) of C = k*G + r*H, such that the parent public key is P = k*G
+    child_key, "bip32-child-key", (k, r), (C, P), (G, H) : C = (k * G + r * H), P = (k * G)
+}
+
+/// Proof that a non-hardened child public key K_i = P + IL*G is derived from the parent private key committed in C = k*G + r*H
+/// (see [`ExtendedPrivateKey::commit`]), and that the prover holds that key, without revealing it
+///
+/// The verifier recomputes the derivation from the parent extended public key (P, c) and the index i, (IL, IR) = HMAC-SHA512(c, P || i),
+/// checks that K_i = P + IL*G, and checks the proof of knowledge of k and r such that C = k*G + r*H and P = k*G:
+/// the holder of the committed parent key controls the child key k + IL.
+/// C and P are hashed into the challenge, so the proof can't be moved to another parent or commitment.
+///
+/// Hardened children aren't supported: their IL is the HMAC of the parent private key, which can't be recomputed by the verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChildKeyProof(child_key::Proof);
+impl ChildKeyProof {
+    /// Prove that the non-hardened child `index` of `parent` is derived from the parent key committed with `blinding`
+    #[cfg(feature = "std")]
+    pub fn prove(
+        sid: &str,
+        pid: u64,
+        parent: &ExtendedPrivateKey,
+        blinding: &Scalar,
+        index: u32,
+    ) -> Result<Self, Bip32Error> {
+        Self::prove_with_rng(sid, pid, parent, blinding, index, &mut rand::thread_rng())
+    }
+
+    pub fn prove_with_rng(
+        sid: &str,
+        pid: u64,
+        parent: &ExtendedPrivateKey,
+        blinding: &Scalar,
+        index: u32,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, Bip32Error> {
+        // the child must be derivable from the public key, as by the verifier
+        parent.public_key().derive_child(index)?;
+        let pc_gens = PedersenGens::default();
+        let assignments = child_key::ProveAssignments {
+            k: &parent.key,
+            r: blinding,
+            C: &parent.commit(blinding),
+            P: &parent.public_key_point(),
+            G: &GENERATOR,
+            H: &pc_gens.b_blinding,
+        };
+        Ok(Self(child_key::prove_with_rng(sid, pid, assignments, rng)))
+    }
+
+    /// Check that `child_public_key` is the non-hardened child `index` of `parent`,
+    /// whose private key is committed in `parent_commitment` and known to the prover
+    pub fn verify(
+        &self,
+        sid: &str,
+        pid: u64,
+        parent: &ExtendedPublicKey,
+        parent_commitment: &ProjectivePoint,
+        index: u32,
+        child_public_key: &ProjectivePoint,
+    ) -> bool {
+        let Ok(child) = parent.derive_child(index) else {
+            return false;
+        };
+        if child.key != *child_public_key {
+            return false;
+        }
+        let pc_gens = PedersenGens::default();
+        let assignments = child_key::VerifyAssignments {
+            C: parent_commitment,
+            P: &parent.key,
+            G: &GENERATOR,
+            H: &pc_gens.b_blinding,
+        };
+        child_key::verify(&self.0, sid, pid, assignments)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        child_key::Proof::from_bytes(bytes).map(Self)
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Bip32Error {
+    #[error("the seed must be 16 to 64 bytes long")]
+    InvalidSeedLength,
+    #[error("the derived key is invalid, proceed with the next index")]
+    InvalidChildKey,
+    #[error("hardened children can't be derived from a public key")]
+    HardenedFromPublicKey,
+    #[error("the maximum depth of 255 is reached")]
+    MaxDepth,
+    #[error("invalid derivation path")]
+    InvalidPath,
+    #[error("invalid encoding")]
+    InvalidEncoding,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::SCALAR_LEN;
+    use alloc::format;
+    use alloc::string::ToString;
+    use rand::thread_rng;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Check the xprv and xpub of every (path, xprv, xpub) of a BIP-32 test vector, derived from the master key
+    /// (and from the parent xpub, when the last index isn't hardened)
+    fn check_test_vector(seed: &str, chains: &[(&str, &str, &str)]) {
+        let master = ExtendedPrivateKey::new_master(&hex(seed)).unwrap();
+        for (path, xprv, xpub) in chains {
+            let path: DerivationPath = path.parse().unwrap();
+            let key = master.derive_path(&path).unwrap();
+            assert_eq!(key.to_base58(), *xprv, "xprv of {path}");
+            assert_eq!(key.public_key().to_base58(), *xpub, "xpub of {path}");
+
+            if let Some((&last, parent_path)) = path.indices().split_last() {
+                let parent = master
+                    .derive_path(&DerivationPath::new(parent_path.to_vec()))
+                    .unwrap();
+                let from_public = parent.public_key().derive_child(last);
+                if last >= HARDENED {
+                    assert_eq!(from_public, Err(Bip32Error::HardenedFromPublicKey));
+                } else {
+                    assert_eq!(from_public.unwrap().to_base58(), *xpub, "CKDpub of {path}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bip32_test_vector_1() {
+        check_test_vector("000102030405060708090a0b0c0d0e0f", &[
+            ("m", "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi", "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"),
+            ("m/0'", "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7", "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw"),
+            ("m/0'/1", "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs", "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ"),
+            ("m/0'/1/2'", "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM", "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5"),
+            ("m/0'/1/2'/2", "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334", "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV"),
+            ("m/0'/1/2'/2/1000000000", "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76", "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"),
+        ]);
+    }
+
+    #[test]
+    fn bip32_test_vector_2() {
+        check_test_vector("fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542", &[
+            ("m", "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U", "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB"),
+            ("m/0", "xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt", "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH"),
+            ("m/0/2147483647'", "xprv9wSp6B7kry3Vj9m1zSnLvN3xH8RdsPP1Mh7fAaR7aRLcQMKTR2vidYEeEg2mUCTAwCd6vnxVrcjfy2kRgVsFawNzmjuHc2YmYRmagcEPdU9", "xpub6ASAVgeehLbnwdqV6UKMHVzgqAG8Gr6riv3Fxxpj8ksbH9ebxaEyBLZ85ySDhKiLDBrQSARLq1uNRts8RuJiHjaDMBU4Zn9h8LZNnBC5y4a"),
+            ("m/0/2147483647'/1", "xprv9zFnWC6h2cLgpmSA46vutJzBcfJ8yaJGg8cX1e5StJh45BBciYTRXSd25UEPVuesF9yog62tGAQtHjXajPPdbRCHuWS6T8XA2ECKADdw4Ef", "xpub6DF8uhdarytz3FWdA8TvFSvvAh8dP3283MY7p2V4SeE2wyWmG5mg5EwVvmdMVCQcoNJxGoWaU9DCWh89LojfZ537wTfunKau47EL2dhHKon"),
+            ("m/0/2147483647'/1/2147483646'", "xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc", "xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL"),
+            ("m/0/2147483647'/1/2147483646'/2", "xprvA2nrNbFZABcdryreWet9Ea4LvTJcGsqrMzxHx98MMrotbir7yrKCEXw7nadnHM8Dq38EGfSh6dqA9QWTyefMLEcBYJUuekgW4BYPJcr9E7j", "xpub6FnCn6nSzZAw5Tw7cgR9bi15UV96gLZhjDstkXXxvCLsUXBGXPdSnLFbdpq8p9HmGsApME5hQTZ3emM2rnY5agb9rXpVGyy3bdW6EEgAtqt"),
+        ]);
+    }
+
+    /// Retention of leading zeros of the private key
+    #[test]
+    fn bip32_test_vector_3() {
+        check_test_vector("4b381541583be4423346c643850da4b320e46a87ae3d2a4e6da11eba819cd4acba45d239319ac14f863b8d5ab5a0d0c64d2e8a1e7d1457df2e5a3c51c73235be", &[
+            ("m", "xprv9s21ZrQH143K25QhxbucbDDuQ4naNntJRi4KUfWT7xo4EKsHt2QJDu7KXp1A3u7Bi1j8ph3EGsZ9Xvz9dGuVrtHHs7pXeTzjuxBrCmmhgC6", "xpub661MyMwAqRbcEZVB4dScxMAdx6d4nFc9nvyvH3v4gJL378CSRZiYmhRoP7mBy6gSPSCYk6SzXPTf3ND1cZAceL7SfJ1Z3GC8vBgp2epUt13"),
+            ("m/0'", "xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L", "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y"),
+        ]);
+    }
+
+    /// Retention of leading zeros of the private key, through hardened derivation
+    #[test]
+    fn bip32_test_vector_4() {
+        check_test_vector("3ddd5602285899a946114506157c7997e5444528f3003f6134712147db19b678", &[
+            ("m", "xprv9s21ZrQH143K48vGoLGRPxgo2JNkJ3J3fqkirQC2zVdk5Dgd5w14S7fRDyHH4dWNHUgkvsvNDCkvAwcSHNAQwhwgNMgZhLtQC63zxwhQmRv", "xpub661MyMwAqRbcGczjuMoRm6dXaLDEhW1u34gKenbeYqAix21mdUKJyuyu5F1rzYGVxyL6tmgBUAEPrEz92mBXjByMRiJdba9wpnN37RLLAXa"),
+            ("m/0'", "xprv9vB7xEWwNp9kh1wQRfCCQMnZUEG21LpbR9NPCNN1dwhiZkjjeGRnaALmPXCX7SgjFTiCTT6bXes17boXtjq3xLpcDjzEuGLQBM5ohqkao9G", "xpub69AUMk3qDBi3uW1sXgjCmVjJ2G6WQoYSnNHyzkmdCHEhSZ4tBok37xfFEqHd2AddP56Tqp4o56AePAgCjYdvpW2PU2jbUPFKsav5ut6Ch1m"),
+            ("m/0'/1'", "xprv9xJocDuwtYCMNAo3Zw76WENQeAS6WGXQ55RCy7tDJ8oALr4FWkuVoHJeHVAcAqiZLE7Je3vZJHxspZdFHfnBEjHqU5hG1Jaj32dVoS6XLT1", "xpub6BJA1jSqiukeaesWfxe6sNK9CCGaujFFSJLomWHprUL9DePQ4JDkM5d88n49sMGJxrhpjazuXYWdMf17C9T5XnxkopaeS7jGk1GyyVziaMt"),
+        ]);
+    }
+
+    /// Invalid extended keys
+    #[test]
+    fn bip32_test_vector_5() {
+        for (encoded, reason) in [
+            ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6LBpB85b3D2yc8sfvZU521AAwdZafEz7mnzBBsz4wKY5fTtTQBm", "pubkey version / prvkey mismatch"),
+            ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGTQQD3dC4H2D5GBj7vWvSQaaBv5cxi9gafk7NF3pnBju6dwKvH", "prvkey version / pubkey mismatch"),
+            ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Txnt3siSujt9RCVYsx4qHZGc62TG4McvMGcAUjeuwZdduYEvFn", "invalid pubkey prefix 04"),
+            ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGpWnsj83BHtEy5Zt8CcDr1UiRXuWCmTQLxEK9vbz5gPstX92JQ", "invalid prvkey prefix 04"),
+            ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6N8ZMMXctdiCjxTNq964yKkwrkBJJwpzZS4HS2fxvyYUA4q2Xe4", "invalid pubkey prefix 01"),
+            ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD9y5gkZ6Eq3Rjuahrv17fEQ3Qen6J", "invalid prvkey prefix 01"),
+            ("xprv9s2SPatNQ9Vc6GTbVMFPFo7jsaZySyzk7L8n2uqKXJen3KUmvQNTuLh3fhZMBoG3G4ZW1N2kZuHEPY53qmbZzCHshoQnNf4GvELZfqTUrcv", "zero depth with non-zero parent fingerprint"),
+            ("xpub661no6RGEX3uJkY4bNnPcw4URcQTrSibUZ4NqJEw5eBkv7ovTwgiT91XX27VbEXGENhYRCf7hyEbWrR3FewATdCEebj6znwMfQkhRYHRLpJ", "zero depth with non-zero parent fingerprint"),
+            ("xprv9s21ZrQH4r4TsiLvyLXqM9P7k1K3EYhA1kkD6xuquB5i39AU8KF42acDyL3qsDbU9NmZn6MsGSUYZEsuoePmjzsB3eFKSUEh3Gu1N3cqVUN", "zero depth with non-zero index"),
+            ("xpub661MyMwAuDcm6CRQ5N4qiHKrJ39Xe1R1NyfouMKTTWcguwVcfrZJaNvhpebzGerh7gucBvzEQWRugZDuDXjNDRmXzSZe4c7mnTK97pTvGS8", "zero depth with non-zero index"),
+            ("DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHGMQzT7ayAmfo4z3gY5KfbrZWZ6St24UVf2Qgo6oujFktLHdHY4", "unknown extended key version"),
+            ("DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHPmHJiEDXkTiJTVV9rHEBUem2mwVbbNfvT2MTcAqj3nesx8uBf9", "unknown extended key version"),
+            ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzF93Y5wvzdUayhgkkFoicQZcP3y52uPPxFnfoLZB21Teqt1VvEHx", "private key 0 not in 1..n-1"),
+            ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD5SDKr24z3aiUvKr9bJpdrcLg1y3G", "private key n not in 1..n-1"),
+            ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Q5JXayek4PRsn35jii4veMimro1xefsM58PgBMrvdYre8QyULY", "invalid pubkey 020000000000000000000000000000000000000000000000000000000000000007"),
+            ("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHL", "invalid checksum"),
+        ] {
+            assert_eq!(ExtendedPrivateKey::from_base58(encoded).err(), Some(Bip32Error::InvalidEncoding), "{reason}");
+            assert_eq!(ExtendedPublicKey::from_base58(encoded), Err(Bip32Error::InvalidEncoding), "{reason}");
+        }
+    }
+
+    #[test]
+    fn base58_roundtrip_and_invalid_encodings() {
+        let key = ExtendedPrivateKey::random_with_rng(&mut thread_rng())
+            .derive_path(&"m/1'/2".parse().unwrap())
+            .unwrap();
+        let xprv = key.to_base58();
+        let xpub = key.public_key().to_base58();
+        assert_eq!(
+            ExtendedPrivateKey::from_base58(&xprv).unwrap().to_base58(),
+            xprv
+        );
+        assert_eq!(ExtendedPublicKey::from_base58(&xpub), Ok(key.public_key()));
+
+        // version of the other kind of key, checksum error, truncation
+        assert!(ExtendedPublicKey::from_base58(&xprv).is_err());
+        assert!(ExtendedPrivateKey::from_base58(&xpub).is_err());
+        let mut corrupted = xpub.clone();
+        corrupted.replace_range(20..21, if &xpub[20..21] == "a" { "b" } else { "a" });
+        assert_eq!(
+            ExtendedPublicKey::from_base58(&corrupted),
+            Err(Bip32Error::InvalidEncoding)
+        );
+        assert!(ExtendedPublicKey::from_base58(&xpub[..xpub.len() - 1]).is_err());
+
+        assert!(!format!("{key:?}").contains(&format!("{:?}", key.expose_secret())));
+    }
+
+    #[test]
+    fn derivation_path_parsing() {
+        let path: DerivationPath = "m/44'/0h/2147483647/1".parse().unwrap();
+        assert_eq!(path.indices(), &[44 + HARDENED, HARDENED, 2147483647, 1]);
+        assert_eq!(path.to_string(), "m/44'/0'/2147483647/1");
+        assert_eq!("m".parse(), Ok(DerivationPath::default()));
+
+        for invalid in [
+            "",
+            "0/1",
+            "m/",
+            "m//1",
+            "m/2147483648",
+            "m/-1",
+            "m/+1",
+            "m/1''",
+            "m/x",
+        ] {
+            assert_eq!(
+                invalid.parse::<DerivationPath>(),
+                Err(Bip32Error::InvalidPath),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn child_key_proof() {
+        let parent = ExtendedPrivateKey::random_with_rng(&mut thread_rng())
+            .derive_child(HARDENED)
+            .unwrap();
+        let parent_public_key = parent.public_key();
+        let blinding = Scalar::generate_vartime(&mut thread_rng());
+        let commitment = parent.commit(&blinding);
+        let index = 7;
+        let child_public_key = *parent
+            .derive_child(index)
+            .unwrap()
+            .public_key()
+            .public_key();
+
+        let proof = ChildKeyProof::prove("sid", 1, &parent, &blinding, index).unwrap();
+        fn verify(
+            proof: &ChildKeyProof,
+            parent: &ExtendedPublicKey,
+            commitment: &ProjectivePoint,
+            index: u32,
+            child: &ProjectivePoint,
+        ) -> bool {
+            proof.verify("sid", 1, parent, commitment, index, child)
+        }
+        assert!(verify(
+            &proof,
+            &parent_public_key,
+            &commitment,
+            index,
+            &child_public_key
+        ));
+        let from_bytes = ChildKeyProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(from_bytes, proof);
+        assert!(verify(
+            &from_bytes,
+            &parent_public_key,
+            &commitment,
+            index,
+            &child_public_key
+        ));
+
+        // a key that isn't the child `index` of the parent: another index, an unrelated key offset from the parent
+        let sibling = *parent
+            .derive_child(index + 1)
+            .unwrap()
+            .public_key()
+            .public_key();
+        assert!(!verify(
+            &proof,
+            &parent_public_key,
+            &commitment,
+            index,
+            &sibling
+        ));
+        assert!(!verify(
+            &proof,
+            &parent_public_key,
+            &commitment,
+            index + 1,
+            &child_public_key
+        ));
+        let offset = child_public_key + GENERATOR;
+        assert!(!verify(
+            &proof,
+            &parent_public_key,
+            &commitment,
+            index,
+            &offset
+        ));
+
+        // another parent commitment, another parent, another session or party
+        let other_commitment = parent.commit(&(blinding + Scalar::ONE));
+        assert!(!verify(
+            &proof,
+            &parent_public_key,
+            &other_commitment,
+            index,
+            &child_public_key
+        ));
+        let other_parent = ExtendedPrivateKey::random_with_rng(&mut thread_rng());
+        let other_child = *other_parent
+            .derive_child(index)
+            .unwrap()
+            .public_key()
+            .public_key();
+        assert!(!verify(
+            &proof,
+            &other_parent.public_key(),
+            &commitment,
+            index,
+            &other_child
+        ));
+        assert!(!proof.verify(
+            "other sid",
+            1,
+            &parent_public_key,
+            &commitment,
+            index,
+            &child_public_key
+        ));
+        assert!(!proof.verify(
+            "sid",
+            2,
+            &parent_public_key,
+            &commitment,
+            index,
+            &child_public_key
+        ));
+
+        // a proof for a key the prover doesn't hold the parent of
+        let forged = ChildKeyProof::prove("sid", 1, &other_parent, &blinding, index).unwrap();
+        assert!(!verify(
+            &forged,
+            &parent_public_key,
+            &commitment,
+            index,
+            &child_public_key
+        ));
+
+        // the parent private key isn't published
+        let bytes = proof.to_bytes();
+        assert!(!bytes
+            .windows(SCALAR_LEN)
+            .any(|w| w == &parent.expose_secret().to_bytes()[..]));
+
+        // hardened children can't be checked from the parent public key
+        assert_eq!(
+            ChildKeyProof::prove("sid", 1, &parent, &blinding, HARDENED + index),
+            Err(Bip32Error::HardenedFromPublicKey)
+        );
+        let hardened_child = *parent
+            .derive_child(HARDENED + index)
+            .unwrap()
+            .public_key()
+            .public_key();
+        assert!(!verify(
+            &proof,
+            &parent_public_key,
+            &commitment,
+            HARDENED + index,
+            &hardened_child
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::schnorr::{self, SchnorrSignature};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+
+/// Blind Schnorr signatures, to issue anonymous tokens
+///
+/// The signer signs a blinded challenge, and the user unblinds the response into a standard [`SchnorrSignature`] on a message the signer never saw.
+/// Protocol (signer key pair x, X = xG):
+/// 1. Signer commitment: the signer generates a random k, sends R = kG
+/// 2. User blinding: the user generates random blinding factors a, b, computes R' = R + aG + bX, c' = H(R', X, m), and sends the blinded challenge c = c' + b
+/// 3. Signer response: s = k + c * x
+/// 4. User unblinding: the user checks s * G == R + c * X, and computes s' = s + a. (R', s') is a Schnorr signature of m:
+///    s'G == kG + cX + aG == R + aG + bX + c'X == R' + c'X
+///
+/// Unlinkability: for ANY signer transcript (R, c, s) and ANY signature (R', s'), the blinding factors b = c - c', a = s' - s satisfy R' == R + aG + bX,
+/// so the signer's view of an issuance is equally consistent with every token redeemed later.
+///
+/// Security note: a signer running many sessions concurrently is vulnerable to the ROS attack (forging one more signature than it issued).
+/// Signers should complete (or abort) each session before opening the next one.
+pub struct BlindSigner {
+    x: Scalar,
+    public_key: ProjectivePoint,
+}
+impl BlindSigner {
+    pub fn new(x: Scalar) -> Self {
+        Self {
+            x,
+            public_key: ProjectivePoint::mul_by_generator(&x),
+        }
+    }
+
+    pub fn public_key(&self) -> ProjectivePoint {
+        self.public_key
+    }
+
+    /// Step 1: open a signing session, and get the commitment R to send to the user
+    #[cfg(feature = "std")]
+    pub fn commit(&self) -> (SignerSession<'_>, ProjectivePoint) {
+        self.commit_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn commit_with_rng(
+        &self,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (SignerSession<'_>, ProjectivePoint) {
+        let k = Scalar::generate_vartime(rng);
+        let r = ProjectivePoint::mul_by_generator(&k);
+        (SignerSession { signer: self, k }, r)
+    }
+}
+
+/// A signer session waiting for the user's blinded challenge. The nonce k is used only once: responding consumes the session
+pub struct SignerSession<'a> {
+    signer: &'a BlindSigner,
+    k: Scalar,
+}
+impl SignerSession<'_> {
+    /// Step 3: answer the user's blinded challenge c with s = k + c * x
+    pub fn respond(self, blinded_challenge: Scalar) -> Scalar {
+        self.k + blinded_challenge * self.signer.x
+    }
+}
+
+/// The user side of the protocol, holding the blinding factors between the challenge and the signer's response
+pub struct UserSession {
+    sid: String,
+    pid: u64,
+    signer_public_key: ProjectivePoint,
+    msg: Vec<u8>,
+    r: ProjectivePoint,
+    blinded_challenge: Scalar,
+    a: Scalar,
+    r_prime: ProjectivePoint,
+}
+impl UserSession {
+    /// Step 2: blind the signer's commitment R, and get the blinded challenge c to send to the signer
+    #[cfg(feature = "std")]
+    pub fn blind(
+        sid: &str,
+        pid: u64,
+        signer_public_key: ProjectivePoint,
+        msg: &[u8],
+        r: ProjectivePoint,
+    ) -> (Self, Scalar) {
+        Self::blind_with_rng(sid, pid, signer_public_key, msg, r, &mut rand::thread_rng())
+    }
+
+    pub fn blind_with_rng(
+        sid: &str,
+        pid: u64,
+        signer_public_key: ProjectivePoint,
+        msg: &[u8],
+        r: ProjectivePoint,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Self, Scalar) {
+        let a = Scalar::generate_vartime(rng);
+        let b = Scalar::generate_vartime(rng);
+
+        let r_prime = r + ProjectivePoint::mul_by_generator(&a) + signer_public_key * b;
+        let c_prime = schnorr::challenge(sid, pid, &r_prime, &signer_public_key, msg);
+        let blinded_challenge = c_prime + b;
+
+        let session = Self {
+            sid: sid.to_string(),
+            pid,
+            signer_public_key,
+            msg: msg.to_vec(),
+            r,
+            blinded_challenge,
+            a,
+            r_prime,
+        };
+        (session, blinded_challenge)
+    }
+
+    /// Step 4: check the signer's response, and unblind it into a standard Schnorr signature of the message
+    pub fn unblind(self, s: Scalar) -> Result<SchnorrSignature, BlindSigError> {
+        if ProjectivePoint::mul_by_generator(&s)
+            != self.r + self.signer_public_key * self.blinded_challenge
+        {
+            return Err(BlindSigError::InvalidSignerResponse);
+        }
+        let signature = SchnorrSignature {
+            r: self.r_prime,
+            s: s + self.a,
+        };
+        debug_assert!(signature.verify(&self.sid, self.pid, &self.msg, &self.signer_public_key));
+        Ok(signature)
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum BlindSigError {
+    #[error("the signer's response doesn't match its commitment and the blinded challenge")]
+    InvalidSignerResponse,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    /// What the signer sees of one issuance
+    struct SignerView {
+        r: ProjectivePoint,
+        c: Scalar,
+        s: Scalar,
+    }
+
+    fn issue(signer: &BlindSigner, msg: &[u8]) -> (SignerView, SchnorrSignature) {
+        let (signer_session, r) = signer.commit();
+        let (user_session, c) = UserSession::blind("tokens", 0, signer.public_key(), msg, r);
+        let s = signer_session.respond(c);
+        let token = user_session.unblind(s).unwrap();
+        (SignerView { r, c, s }, token)
+    }
+
+    #[test]
+    fn unblinded_signature_is_a_valid_schnorr_signature() {
+        let signer = BlindSigner::new(Scalar::generate_vartime(&mut thread_rng()));
+        let (_, token) = issue(&signer, b"token serial 1");
+
+        assert!(token.verify("tokens", 0, b"token serial 1", &signer.public_key()));
+        assert!(!token.verify("tokens", 0, b"token serial 2", &signer.public_key()));
+    }
+
+    #[test]
+    fn unblind_rejects_invalid_signer_response() {
+        let signer = BlindSigner::new(Scalar::generate_vartime(&mut thread_rng()));
+        let (signer_session, r) = signer.commit();
+        let (user_session, c) = UserSession::blind("tokens", 0, signer.public_key(), b"msg", r);
+        let s = signer_session.respond(c + Scalar::ONE);
+
+        assert_eq!(
+            user_session.unblind(s),
+            Err(BlindSigError::InvalidSignerResponse)
+        );
+    }
+
+    #[test]
+    fn signer_cannot_link_issued_tokens_to_redeemed_ones() {
+        let signer = BlindSigner::new(Scalar::generate_vartime(&mut thread_rng()));
+        let x = signer.public_key();
+        let msgs: [&[u8]; 2] = [b"token serial 1", b"token serial 2"];
+        let (views, tokens): (Vec<_>, Vec<_>) = msgs.iter().map(|msg| issue(&signer, msg)).unzip();
+
+        // the issued transcripts never appear in the redeemed tokens
+        for (view, token) in views.iter().zip(&tokens) {
+            assert_ne!(view.r, *token.r());
+            assert_ne!(view.s, *token.s());
+        }
+
+        // every (transcript, token) pairing, including the "wrong" ones, is explained by some blinding factors,
+        // so the signer's view carries no information about which issuance produced which token
+        for view in &views {
+            for (msg, token) in msgs.iter().zip(&tokens) {
+                let c_prime = schnorr::challenge("tokens", 0, token.r(), &x, msg);
+                let b = view.c - c_prime;
+                let a = token.s() - &view.s;
+                assert_eq!(
+                    *token.r(),
+                    view.r + ProjectivePoint::mul_by_generator(&a) + x * b
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// ElGamal encryption of curve points, under a public key PK = sk * G
+///
+/// Encryption of M with randomness r: (A, B) = (M + r * PK, r * G). Decryption: M = A - sk * B.
+/// Anyone can re-encrypt a ciphertext with fresh randomness r': (A + r' * PK, B + r' * G) decrypts to the same M but can't be linked to the original,
+/// which is what a mix server relies on (see [`ShuffleProof`](crate::shuffle::ShuffleProof)).
+///
+/// Messages are points: encode small integers (e.g. a vote for candidate v) as v * G, and recover them with a lookup after decryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ciphertext {
+    /// A = M + r * PK
+    #[serde(with = "crate::encoding::serde_point")]
+    pub a: ProjectivePoint,
+    /// B = r * G
+    #[serde(with = "crate::encoding::serde_point")]
+    pub b: ProjectivePoint,
+}
+impl Ciphertext {
+    /// Encryption of `msg` under `public_key` with randomness r
+    pub fn encrypt(public_key: &ProjectivePoint, msg: &ProjectivePoint, r: &Scalar) -> Self {
+        Self {
+            a: *msg + public_key * r,
+            b: ProjectivePoint::mul_by_generator(r),
+        }
+    }
+
+    pub fn encrypt_with_rng(
+        public_key: &ProjectivePoint,
+        msg: &ProjectivePoint,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        Self::encrypt(public_key, msg, &Scalar::generate_vartime(rng))
+    }
+
+    /// Same message, fresh randomness: (A + r * PK, B + r * G)
+    pub fn reencrypt(&self, public_key: &ProjectivePoint, r: &Scalar) -> Self {
+        Self {
+            a: self.a + public_key * r,
+            b: self.b + ProjectivePoint::mul_by_generator(r),
+        }
+    }
+
+    /// M = A - sk * B
+    pub fn decrypt(&self, secret_key: &Scalar) -> ProjectivePoint {
+        self.a - self.b * secret_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn reencryption_decrypts_to_the_same_message() {
+        let sk = Scalar::generate_vartime(&mut thread_rng());
+        let pk = ProjectivePoint::mul_by_generator(&sk);
+        let msg = ProjectivePoint::mul_by_generator(&Scalar::from(3u64));
+
+        let ciphertext = Ciphertext::encrypt_with_rng(&pk, &msg, &mut thread_rng());
+        let reencrypted = ciphertext.reencrypt(&pk, &Scalar::generate_vartime(&mut thread_rng()));
+        assert_ne!(reencrypted, ciphertext);
+        assert_eq!(ciphertext.decrypt(&sk), msg);
+        assert_eq!(reencrypted.decrypt(&sk), msg);
+        assert_ne!(reencrypted.decrypt(&(sk + Scalar::ONE)), msg);
+    }
+}
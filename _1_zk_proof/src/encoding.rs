@@ -0,0 +1,91 @@
+//! Byte and hex encodings of curve points and scalars, shared by the proofs and the bindings
+use alloc::string::String;
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::PrimeField;
+use k256::{AffinePoint, CompressedPoint, ProjectivePoint, Scalar};
+
+/// Size of a curve point in compressed SEC1 encoding (1 byte tag + 32 bytes x-coordinate)
+pub const POINT_LEN: usize = 33;
+/// Size of a scalar in big-endian encoding
+pub const SCALAR_LEN: usize = 32;
+
+/// Decode a compressed SEC1 point. The identity point is rejected: it is never a valid key or commitment
+pub fn point_from_bytes(bytes: &[u8]) -> Option<ProjectivePoint> {
+    if bytes.len() != POINT_LEN {
+        return None;
+    }
+    let point = Option::<ProjectivePoint>::from(ProjectivePoint::from_bytes(
+        CompressedPoint::from_slice(bytes),
+    ))?;
+    (point != ProjectivePoint::IDENTITY).then_some(point)
+}
+
+/// Decode a big-endian scalar, rejecting non-canonical encodings (>= curve order)
+pub fn scalar_from_bytes(bytes: &[u8]) -> Option<Scalar> {
+    if bytes.len() != SCALAR_LEN {
+        return None;
+    }
+    Option::from(Scalar::from_repr(*k256::FieldBytes::from_slice(bytes)))
+}
+
+/// Hex of the compressed SEC1 encoding (33 bytes)
+pub fn point_to_hex(point: &ProjectivePoint) -> String {
+    hex::encode(point.to_bytes())
+}
+
+/// Same as [`point_from_bytes`], from hex
+pub fn point_from_hex(point: &str) -> Option<ProjectivePoint> {
+    point_from_bytes(&hex::decode(point).ok()?)
+}
+
+/// Hex of the big-endian encoding (32 bytes)
+pub fn scalar_to_hex(scalar: &Scalar) -> String {
+    hex::encode(scalar.to_bytes())
+}
+
+/// Same as [`scalar_from_bytes`], from hex
+pub fn scalar_from_hex(scalar: &str) -> Option<Scalar> {
+    scalar_from_bytes(&hex::decode(scalar).ok()?)
+}
+
+/// serde helpers for `ProjectivePoint`, which k256 only implements serde for in affine form
+/// Use with `#[serde(with = "crate::encoding::serde_point")]`
+///
+/// Like [`point_from_bytes`], the identity point is rejected
+pub mod serde_point {
+    use super::*;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &ProjectivePoint, s: S) -> Result<S::Ok, S::Error> {
+        AffinePoint::from(*point).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ProjectivePoint, D::Error> {
+        non_identity(AffinePoint::deserialize(d)?)
+    }
+
+    pub(super) fn non_identity<E: Error>(point: AffinePoint) -> Result<ProjectivePoint, E> {
+        if point == AffinePoint::IDENTITY {
+            return Err(E::custom("the identity point is not a valid point"));
+        }
+        Ok(point.into())
+    }
+}
+
+/// Same as [`serde_point`], for a list of points
+pub mod serde_points {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(points: &[ProjectivePoint], s: S) -> Result<S::Ok, S::Error> {
+        let affine: Vec<AffinePoint> = points.iter().map(|p| AffinePoint::from(*p)).collect();
+        affine.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<ProjectivePoint>, D::Error> {
+        let affine = Vec::<AffinePoint>::deserialize(d)?;
+        affine.into_iter().map(serde_point::non_identity).collect()
+    }
+}
@@ -0,0 +1,648 @@
+//! FROST-style threshold Schnorr signatures, producing BIP-340 signatures
+//!
+//! The two rounds follow RFC 9591, but not its wire format: the binding factors and nonces are hashed with this crate's transcript
+//! instead of the RFC's H1-H5, and the challenge is BIP-340's. Its commitments and shares don't interoperate with RFC 9591 implementations,
+//! only its signatures do, with any BIP-340 verifier.
+//!
+//! A group key Y = xG is split into Shamir shares x_i = f(i) of a degree t-1 polynomial f with f(0) = x, one per party id i in 1..=n:
+//! any t parties can sign together, fewer learn nothing about x.
+//! Two rounds, for a signer set S of at least t parties:
+//! 1. Commitment: each signer i generates nonces (d_i, e_i), and publishes (D_i, E_i) = (d_i*G, e_i*G)
+//! 2. Signature share: the coordinator sends the message and all the commitments to the signers. Each one computes
+//!    - its binding factor ρ_i = H(sid, i, [Y, D_j, E_j for j in S], msg), which ties its nonce to this message and signer set
+//!    - the group commitment R = Σ_j (D_j + ρ_j * E_j)
+//!    - the BIP-340 challenge c = H_BIP0340/challenge(x(R) || x(Y) || msg)
+//!    - z_i = d_i + ρ_i * e_i + λ_i * x_i * c, where λ_i is its Lagrange coefficient in S
+//! 3. Aggregation: z = Σ z_i, and (x(R), z) is a BIP-340 signature by Y: z*G = R + c*Y
+//!
+//! Identifiable aborts: the coordinator checks every share, z_i * G == D_i + ρ_i * E_i + c * λ_i * Y_i with the public verification share Y_i = x_i*G,
+//! so a signer sending a wrong share is identified (see [`FrostError::InvalidSignatureShare`]).
+//!
+//! BIP-340 keys and nonces have an even y coordinate: the dealer negates x if Y is odd, and the signers negate their nonces if R is odd.
+//! Keys are generated by a trusted dealer ([`generate_with_rng`]), who knows x and must erase it once the shares are distributed.
+use crate::encoding;
+use crate::transcript;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use k256::elliptic_curve::ops::{MulByGenerator, Reduce};
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::elliptic_curve::zeroize::Zeroize;
+use k256::elliptic_curve::Field;
+use k256::{ProjectivePoint, Scalar, U256};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const BINDING_FACTOR_LABEL: &[u8] = b"_1_zk_proof-frost-binding-factor-v1";
+const NONCE_LABEL: &[u8] = b"_1_zk_proof-frost-nonce-v1";
+
+/// Secret share of a signer
+///
+/// The share is wiped from memory when dropped, and not printed by `Debug`
+#[derive(Clone)]
+pub struct KeyShare {
+    pid: u64,
+    secret_share: Scalar,
+    group_public_key: ProjectivePoint,
+    threshold: usize,
+}
+impl KeyShare {
+    pub fn pid(&self) -> u64 {
+        self.pid
+    }
+
+    pub fn group_public_key(&self) -> &ProjectivePoint {
+        &self.group_public_key
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Y_i = x_i * G
+    pub fn verifying_share(&self) -> ProjectivePoint {
+        ProjectivePoint::mul_by_generator(&self.secret_share)
+    }
+}
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.secret_share.zeroize();
+    }
+}
+impl fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("pid", &self.pid)
+            .field("group_public_key", &self.group_public_key)
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Public keys of a signing group: what the coordinator needs to check the signature shares
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyPackage {
+    #[serde(with = "encoding::serde_point")]
+    group_public_key: ProjectivePoint,
+    /// Y_i = x_i * G, by party id
+    #[serde(with = "serde_point_map")]
+    verifying_shares: BTreeMap<u64, ProjectivePoint>,
+    threshold: usize,
+}
+impl PublicKeyPackage {
+    pub fn group_public_key(&self) -> &ProjectivePoint {
+        &self.group_public_key
+    }
+
+    /// The BIP-340 public key: x coordinate of the group public key
+    pub fn x_only_public_key(&self) -> [u8; 32] {
+        x_only(&self.group_public_key)
+    }
+
+    pub fn verifying_share(&self, pid: u64) -> Option<&ProjectivePoint> {
+        self.verifying_shares.get(&pid)
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+/// Trusted dealer key generation: a random group key split in `n` shares (party ids 1..=n), any `threshold` of which can sign
+#[cfg(feature = "std")]
+pub fn generate(
+    threshold: usize,
+    n: usize,
+) -> Result<(Vec<KeyShare>, PublicKeyPackage), FrostError> {
+    generate_with_rng(threshold, n, &mut rand::thread_rng())
+}
+pub fn generate_with_rng(
+    threshold: usize,
+    n: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(Vec<KeyShare>, PublicKeyPackage), FrostError> {
+    if threshold == 0 || threshold > n {
+        return Err(FrostError::InvalidThreshold);
+    }
+    // f(X) = x + a_1 X + ... + a_{t-1} X^{t-1}
+    let mut coefficients: Vec<Scalar> = (0..threshold).map(|_| Scalar::random(&mut *rng)).collect();
+    let mut group_public_key = ProjectivePoint::mul_by_generator(&coefficients[0]);
+    if has_odd_y(&group_public_key) {
+        // -f shares -x, whose public key -Y has an even y
+        coefficients.iter_mut().for_each(|a| *a = -*a);
+        group_public_key = -group_public_key;
+    }
+
+    let shares: Vec<KeyShare> = (1..=n as u64)
+        .map(|pid| KeyShare {
+            pid,
+            // Horner's method
+            secret_share: coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |acc, a| acc * Scalar::from(pid) + a),
+            group_public_key,
+            threshold,
+        })
+        .collect();
+    coefficients.zeroize();
+
+    let public_keys = PublicKeyPackage {
+        group_public_key,
+        verifying_shares: shares
+            .iter()
+            .map(|share| (share.pid, share.verifying_share()))
+            .collect(),
+        threshold,
+    };
+    Ok((shares, public_keys))
+}
+
+/// Round 1 secret nonces (d_i, e_i): use them for a single signature, [`sign`] consumes them
+///
+/// They are wiped from memory when dropped, and not printed by `Debug`
+pub struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+    commitments: SigningCommitments,
+}
+impl SigningNonces {
+    pub fn commitments(&self) -> &SigningCommitments {
+        &self.commitments
+    }
+}
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+impl fmt::Debug for SigningNonces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningNonces")
+            .field("commitments", &self.commitments)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Round 1 public commitments (D_i, E_i) of a signer, sent to the coordinator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitments {
+    pub pid: u64,
+    #[serde(with = "encoding::serde_point")]
+    hiding: ProjectivePoint,
+    #[serde(with = "encoding::serde_point")]
+    binding: ProjectivePoint,
+}
+
+/// Round 1: generate nonces and their commitments
+///
+/// The nonces hash fresh randomness with the secret share (like RFC 9591's nonce_generate), so that they stay secret even with a weak RNG
+#[cfg(feature = "std")]
+pub fn commit(key_share: &KeyShare) -> (SigningNonces, SigningCommitments) {
+    commit_with_rng(key_share, &mut rand::thread_rng())
+}
+pub fn commit_with_rng(
+    key_share: &KeyShare,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> (SigningNonces, SigningCommitments) {
+    let mut nonce = || {
+        let mut random = [0u8; 32];
+        rng.fill_bytes(&mut random);
+        let mut msg = [NONCE_LABEL, &random, &key_share.secret_share.to_bytes()].concat();
+        let nonce = transcript::challenge(b"", key_share.pid, &[], &msg);
+        random.zeroize();
+        msg.zeroize();
+        nonce
+    };
+    let (hiding, binding) = (nonce(), nonce());
+    let commitments = SigningCommitments {
+        pid: key_share.pid,
+        hiding: ProjectivePoint::mul_by_generator(&hiding),
+        binding: ProjectivePoint::mul_by_generator(&binding),
+    };
+    let nonces = SigningNonces {
+        hiding,
+        binding,
+        commitments,
+    };
+    (nonces, commitments)
+}
+
+/// What the coordinator sends to the signers in round 2: the session id, the message, and the commitments of every signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPackage {
+    sid: String,
+    msg: Vec<u8>,
+    commitments: BTreeMap<u64, SigningCommitments>,
+}
+impl SigningPackage {
+    pub fn new(
+        sid: impl Into<String>,
+        msg: &[u8],
+        commitments: &[SigningCommitments],
+    ) -> Result<Self, FrostError> {
+        let mut by_pid = BTreeMap::new();
+        for commitment in commitments {
+            if commitment.pid == 0 || by_pid.insert(commitment.pid, *commitment).is_some() {
+                return Err(FrostError::InvalidSignerSet);
+            }
+        }
+        Ok(Self {
+            sid: sid.into(),
+            msg: msg.to_vec(),
+            commitments: by_pid,
+        })
+    }
+
+    /// Party ids of the signers, in increasing order
+    pub fn signers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.commitments.keys().copied()
+    }
+
+    pub fn msg(&self) -> &[u8] {
+        &self.msg
+    }
+
+    /// The group commitment R (negated to an even y coordinate), the BIP-340 challenge,
+    /// and for each signer its commitment D_i + ρ_i * E_i (negated with R) and its binding factor
+    fn group_commitment(&self, group_public_key: &ProjectivePoint) -> GroupCommitment {
+        let mut points = alloc::vec![*group_public_key];
+        for commitment in self.commitments.values() {
+            points.extend([commitment.hiding, commitment.binding]);
+        }
+        let msg = [BINDING_FACTOR_LABEL, &self.msg].concat();
+
+        let mut signer_commitments: BTreeMap<u64, (ProjectivePoint, Scalar)> = self
+            .commitments
+            .values()
+            .map(|commitment| {
+                let rho = transcript::challenge(self.sid.as_bytes(), commitment.pid, &points, &msg);
+                (
+                    commitment.pid,
+                    (commitment.hiding + commitment.binding * rho, rho),
+                )
+            })
+            .collect();
+        let mut r = signer_commitments
+            .values()
+            .map(|(r_i, _)| r_i)
+            .sum::<ProjectivePoint>();
+        let negate_nonces = has_odd_y(&r);
+        if negate_nonces {
+            r = -r;
+            signer_commitments
+                .values_mut()
+                .for_each(|(r_i, _)| *r_i = -*r_i);
+        }
+        GroupCommitment {
+            challenge: bip340_challenge(&x_only(&r), &x_only(group_public_key), &self.msg),
+            r,
+            negate_nonces,
+            signer_commitments,
+        }
+    }
+}
+
+struct GroupCommitment {
+    r: ProjectivePoint,
+    challenge: Scalar,
+    negate_nonces: bool,
+    /// (R_i, ρ_i) by party id
+    signer_commitments: BTreeMap<u64, (ProjectivePoint, Scalar)>,
+}
+
+/// Round 2 output of a signer: z_i
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureShare {
+    pub pid: u64,
+    z: Scalar,
+}
+
+/// Round 2: sign the package's message, with the nonces generated in round 1 for this package's commitments
+pub fn sign(
+    package: &SigningPackage,
+    nonces: SigningNonces,
+    key_share: &KeyShare,
+) -> Result<SignatureShare, FrostError> {
+    if package.commitments.get(&key_share.pid) != Some(&nonces.commitments) {
+        return Err(FrostError::MissingCommitments { pid: key_share.pid });
+    }
+    if package.commitments.len() < key_share.threshold {
+        return Err(FrostError::NotEnoughSigners);
+    }
+    let group_commitment = package.group_commitment(&key_share.group_public_key);
+    let (_, rho) = group_commitment.signer_commitments[&key_share.pid];
+    let lambda = lagrange_coefficient(key_share.pid, package.signers());
+
+    let mut nonce = nonces.hiding + nonces.binding * rho;
+    if group_commitment.negate_nonces {
+        nonce = -nonce;
+    }
+    let z = nonce + lambda * key_share.secret_share * group_commitment.challenge;
+    nonce.zeroize();
+    Ok(SignatureShare {
+        pid: key_share.pid,
+        z,
+    })
+}
+
+/// Check every signature share, and aggregate them into the BIP-340 signature of the group
+///
+/// Fails with the party id of the first signer whose share is missing or invalid
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &[SignatureShare],
+    public_keys: &PublicKeyPackage,
+) -> Result<Bip340Signature, FrostError> {
+    if package.commitments.len() < public_keys.threshold {
+        return Err(FrostError::NotEnoughSigners);
+    }
+    let shares: BTreeMap<u64, Scalar> = shares.iter().map(|share| (share.pid, share.z)).collect();
+    let group_commitment = package.group_commitment(&public_keys.group_public_key);
+
+    let mut z = Scalar::ZERO;
+    for (&pid, (r_i, _)) in &group_commitment.signer_commitments {
+        let z_i = shares
+            .get(&pid)
+            .ok_or(FrostError::MissingSignatureShare { pid })?;
+        let y_i = public_keys
+            .verifying_shares
+            .get(&pid)
+            .ok_or(FrostError::UnknownSigner { pid })?;
+        let lambda = lagrange_coefficient(pid, package.signers());
+        // z_i * G == R_i + c * λ_i * Y_i
+        if ProjectivePoint::mul_by_generator(z_i)
+            != r_i + &(y_i * &(group_commitment.challenge * lambda))
+        {
+            return Err(FrostError::InvalidSignatureShare { pid });
+        }
+        z += z_i;
+    }
+    Ok(Bip340Signature {
+        r: x_only(&group_commitment.r),
+        s: z,
+    })
+}
+
+/// λ_i = Π_{j in S, j != i} j / (j - i): f(0) = Σ_{i in S} λ_i * f(i) for a polynomial f of degree < |S|
+fn lagrange_coefficient(pid: u64, signers: impl Iterator<Item = u64>) -> Scalar {
+    let (numerator, denominator) =
+        signers
+            .filter(|&j| j != pid)
+            .fold((Scalar::ONE, Scalar::ONE), |(num, den), j| {
+                (
+                    num * Scalar::from(j),
+                    den * (Scalar::from(j) - Scalar::from(pid)),
+                )
+            });
+    numerator * denominator.invert().expect("party ids are distinct")
+}
+
+/// Run both rounds for the `signers` among `key_shares`, all in this process: the harness to test and simulate a signing group on one machine
+#[cfg(feature = "std")]
+pub fn simulate(
+    sid: &str,
+    msg: &[u8],
+    key_shares: &[KeyShare],
+    signers: &[u64],
+    public_keys: &PublicKeyPackage,
+) -> Result<Bip340Signature, FrostError> {
+    simulate_with_rng(
+        sid,
+        msg,
+        key_shares,
+        signers,
+        public_keys,
+        &mut rand::thread_rng(),
+    )
+}
+pub fn simulate_with_rng(
+    sid: &str,
+    msg: &[u8],
+    key_shares: &[KeyShare],
+    signers: &[u64],
+    public_keys: &PublicKeyPackage,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Bip340Signature, FrostError> {
+    let signing_shares = signers
+        .iter()
+        .map(|&pid| {
+            key_shares
+                .iter()
+                .find(|share| share.pid == pid)
+                .ok_or(FrostError::UnknownSigner { pid })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (nonces, commitments): (Vec<_>, Vec<_>) = signing_shares
+        .iter()
+        .map(|share| commit_with_rng(share, rng))
+        .unzip();
+    let package = SigningPackage::new(sid, msg, &commitments)?;
+    let signature_shares = signing_shares
+        .iter()
+        .zip(nonces)
+        .map(|(share, nonces)| sign(&package, nonces, share))
+        .collect::<Result<Vec<_>, _>>()?;
+    aggregate(&package, &signature_shares, public_keys)
+}
+
+/// BIP-340 signature (x(R), s): 64 bytes, verifiable by any BIP-340 implementation (e.g. Bitcoin taproot key path spends)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bip340Signature {
+    r: [u8; 32],
+    s: Scalar,
+}
+impl Bip340Signature {
+    /// Verify with the x-only public key, as in BIP-340
+    pub fn verify(&self, msg: &[u8], public_key: &[u8; 32]) -> bool {
+        // lift_x: the point with this x coordinate and an even y
+        let Some(public_key) = encoding::point_from_bytes(&[&[0x02], &public_key[..]].concat())
+        else {
+            return false;
+        };
+        let c = bip340_challenge(&self.r, &x_only(&public_key), msg);
+        let r = ProjectivePoint::mul_by_generator(&self.s) - public_key * c;
+        r != ProjectivePoint::IDENTITY && !has_odd_y(&r) && x_only(&r) == self.r
+    }
+
+    /// x(R) || s
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 64 {
+            return None;
+        }
+        Some(Self {
+            r: bytes[..32].try_into().expect("32 bytes"),
+            s: encoding::scalar_from_bytes(&bytes[32..])?,
+        })
+    }
+}
+
+/// c = SHA256(SHA256(tag) || SHA256(tag) || x(R) || x(Y) || msg) mod q, with tag "BIP0340/challenge"
+fn bip340_challenge(r: &[u8; 32], public_key: &[u8; 32], msg: &[u8]) -> Scalar {
+    let tag = Sha256::digest(b"BIP0340/challenge");
+    let hash = Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(r)
+        .chain_update(public_key)
+        .chain_update(msg)
+        .finalize();
+    Scalar::reduce(U256::from_be_slice(&hash))
+}
+
+fn x_only(point: &ProjectivePoint) -> [u8; 32] {
+    point.to_affine().x().into()
+}
+
+fn has_odd_y(point: &ProjectivePoint) -> bool {
+    point.to_affine().y_is_odd().into()
+}
+
+/// serde of the verification shares, as a map from party id to compressed point
+mod serde_point_map {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Point(#[serde(with = "encoding::serde_point")] ProjectivePoint);
+
+    pub fn serialize<S: Serializer>(
+        map: &BTreeMap<u64, ProjectivePoint>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_map(map.iter().map(|(pid, point)| (pid, Point(*point))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<BTreeMap<u64, ProjectivePoint>, D::Error> {
+        let map = BTreeMap::<u64, Point>::deserialize(d)?;
+        Ok(map
+            .into_iter()
+            .map(|(pid, Point(point))| (pid, point))
+            .collect())
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum FrostError {
+    #[error("the threshold must be between 1 and the number of parties")]
+    InvalidThreshold,
+    #[error("the signers must have distinct, non-zero party ids")]
+    InvalidSignerSet,
+    #[error("fewer signers than the threshold")]
+    NotEnoughSigners,
+    #[error("party {pid} is not part of the signing group")]
+    UnknownSigner { pid: u64 },
+    #[error("the signing package doesn't contain the round 1 commitments of party {pid}")]
+    MissingCommitments { pid: u64 },
+    #[error("party {pid} didn't send its signature share")]
+    MissingSignatureShare { pid: u64 },
+    #[error("the signature share of party {pid} is invalid")]
+    InvalidSignatureShare { pid: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn threshold_signature_is_a_valid_bip340_signature() {
+        let (key_shares, public_keys) = generate(3, 5).unwrap();
+        let public_key = public_keys.x_only_public_key();
+        let k256_key = k256::schnorr::VerifyingKey::from_bytes(&public_key).unwrap();
+
+        for signers in [&[1, 2, 3][..], &[2, 4, 5], &[1, 2, 3, 4, 5]] {
+            let msg = b"taproot spend";
+            let signature = simulate("sid", msg, &key_shares, signers, &public_keys).unwrap();
+            assert!(signature.verify(msg, &public_key));
+            assert!(!signature.verify(b"other msg", &public_key));
+
+            // checked by an independent BIP-340 implementation
+            let k256_signature =
+                k256::schnorr::Signature::try_from(&signature.to_bytes()[..]).unwrap();
+            assert!(k256_key.verify_raw(msg, &k256_signature).is_ok());
+            assert_eq!(
+                Bip340Signature::from_bytes(&signature.to_bytes()),
+                Some(signature)
+            );
+        }
+    }
+
+    #[test]
+    fn not_enough_signers_or_unknown_signer() {
+        let (key_shares, public_keys) = generate(3, 4).unwrap();
+        assert_eq!(
+            simulate("sid", b"msg", &key_shares, &[1, 2], &public_keys),
+            Err(FrostError::NotEnoughSigners)
+        );
+        assert_eq!(
+            simulate("sid", b"msg", &key_shares, &[1, 2, 7], &public_keys),
+            Err(FrostError::UnknownSigner { pid: 7 })
+        );
+        assert_eq!(generate(5, 4).unwrap_err(), FrostError::InvalidThreshold);
+    }
+
+    #[test]
+    fn invalid_or_missing_share_identifies_the_signer() {
+        let (key_shares, public_keys) = generate(2, 3).unwrap();
+        let signers = [&key_shares[0], &key_shares[2]];
+        let (nonces, commitments): (Vec<_>, Vec<_>) =
+            signers.iter().map(|share| commit(share)).unzip();
+        let package = SigningPackage::new("sid", b"msg", &commitments).unwrap();
+        let mut shares: Vec<SignatureShare> = signers
+            .iter()
+            .zip(nonces)
+            .map(|(share, nonces)| sign(&package, nonces, share).unwrap())
+            .collect();
+        assert!(aggregate(&package, &shares, &public_keys).is_ok());
+
+        shares[1].z += Scalar::ONE;
+        assert_eq!(
+            aggregate(&package, &shares, &public_keys),
+            Err(FrostError::InvalidSignatureShare { pid: 3 })
+        );
+        assert_eq!(
+            aggregate(&package, &shares[..1], &public_keys),
+            Err(FrostError::MissingSignatureShare { pid: 3 })
+        );
+
+        // nonces are bound to the package they were committed for
+        let (nonces, _) = commit(&key_shares[0]);
+        assert_eq!(
+            sign(&package, nonces, &key_shares[0]).unwrap_err(),
+            FrostError::MissingCommitments { pid: 1 }
+        );
+        assert_eq!(
+            SigningPackage::new("sid", b"msg", &[commitments[0], commitments[0]]).unwrap_err(),
+            FrostError::InvalidSignerSet
+        );
+    }
+
+    #[test]
+    fn public_key_package_serde_roundtrip() {
+        let (key_shares, public_keys) = generate(2, 3).unwrap();
+        let json = serde_json::to_string(&public_keys).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PublicKeyPackage>(&json).unwrap(),
+            public_keys
+        );
+        assert_eq!(
+            public_keys.verifying_share(2),
+            Some(&key_shares[1].verifying_share())
+        );
+        assert!(!format!("{:?}", key_shares[0]).contains("secret_share"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::encoding::{self, POINT_LEN, SCALAR_LEN};
+use crate::transcript::Transcript;
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::{ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+
+/// Bulletproofs inner-product argument (Bünz et al. 2017, protocol 2), made non-interactive with the crate's Fiat-Shamir transcript
+///
+/// The prover knows vectors a, b of size n (a power of 2) so that P = <a, G> + <b, H> + <a, b> * Q, and proves it with 2*log2(n) points and 2 scalars
+/// Each round halves the vectors:
+/// 1. Commitment: the prover sends the cross terms L = <a_lo, G_hi> + <b_hi, H_lo> + <a_lo, b_hi> * Q and R = <a_hi, G_lo> + <b_lo, H_hi> + <a_hi, b_lo> * Q
+/// 2. Challenge: x = H(transcript, L, R)
+/// 3. Both sides fold: a' = x*a_lo + x^-1*a_hi, b' = x^-1*b_lo + x*b_hi, G' = x^-1*G_lo + x*G_hi, H' = x*H_lo + x^-1*H_hi, P' = x^2*L + P + x^-2*R
+///
+/// When n == 1, the prover sends a and b, and the verifier checks P == a*G + b*H + a*b*Q.
+/// The verifier doesn't fold the generators round by round: it computes the final G and H directly as Σ s_i*G_i and Σ s_i^-1*H_i,
+/// where s_i is the product of x_j or x_j^-1 depending on the bits of i
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InnerProductProof {
+    #[serde(with = "encoding::serde_points")]
+    l_vec: Vec<ProjectivePoint>,
+    #[serde(with = "encoding::serde_points")]
+    r_vec: Vec<ProjectivePoint>,
+    a: Scalar,
+    b: Scalar,
+}
+impl InnerProductProof {
+    /// `g`, `h`, `a`, `b` must all have the same length, a power of 2
+    pub(crate) fn prove(
+        transcript: &mut Transcript,
+        q: &ProjectivePoint,
+        mut g: Vec<ProjectivePoint>,
+        mut h: Vec<ProjectivePoint>,
+        mut a: Vec<Scalar>,
+        mut b: Vec<Scalar>,
+    ) -> Self {
+        let mut n = g.len();
+        assert!(n.is_power_of_two(), "vector size must be a power of 2");
+        assert!(h.len() == n && a.len() == n && b.len() == n);
+        transcript.append_u64(n as u64);
+
+        let mut l_vec = Vec::with_capacity(n.ilog2() as usize);
+        let mut r_vec = Vec::with_capacity(n.ilog2() as usize);
+        while n > 1 {
+            n /= 2;
+            let (a_lo, a_hi) = a.split_at(n);
+            let (b_lo, b_hi) = b.split_at(n);
+            let (g_lo, g_hi) = g.split_at(n);
+            let (h_lo, h_hi) = h.split_at(n);
+
+            let c_l = inner_product(a_lo, b_hi);
+            let c_r = inner_product(a_hi, b_lo);
+            let l = multiscalar_mul(a_lo, g_hi) + multiscalar_mul(b_hi, h_lo) + q * &c_l;
+            let r = multiscalar_mul(a_hi, g_lo) + multiscalar_mul(b_lo, h_hi) + q * &c_r;
+            transcript.append_point(&l);
+            transcript.append_point(&r);
+            l_vec.push(l);
+            r_vec.push(r);
+
+            let x = transcript.challenge();
+            let x_inv = x.invert().unwrap();
+            a = fold_scalars(a_lo, a_hi, &x, &x_inv);
+            b = fold_scalars(b_lo, b_hi, &x_inv, &x);
+            g = fold_points(g_lo, g_hi, &x_inv, &x);
+            h = fold_points(h_lo, h_hi, &x, &x_inv);
+        }
+
+        Self {
+            l_vec,
+            r_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    /// Verify that the prover knows a, b so that P = <a, G> + <b, H> + <a, b> * Q
+    pub(crate) fn verify(
+        &self,
+        transcript: &mut Transcript,
+        q: &ProjectivePoint,
+        g: &[ProjectivePoint],
+        h: &[ProjectivePoint],
+        p: &ProjectivePoint,
+    ) -> bool {
+        let n = g.len();
+        let rounds = self.l_vec.len();
+        // the number of rounds comes from the proof: compared without shifting by it, which overflows with 64 rounds or more
+        if h.len() != n
+            || !n.is_power_of_two()
+            || n.trailing_zeros() as usize != rounds
+            || self.r_vec.len() != rounds
+        {
+            return false;
+        }
+        transcript.append_u64(n as u64);
+
+        let mut challenges = Vec::with_capacity(rounds);
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            transcript.append_point(l);
+            transcript.append_point(r);
+            challenges.push(transcript.challenge());
+        }
+        let Some(challenges_inv) = challenges
+            .iter()
+            .map(|x| Option::<Scalar>::from(x.invert()))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+
+        // s_i = Π_j x_j^(±1): round j splits on bit (rounds - 1 - j) of i, the high half being multiplied by x_j
+        let s: Vec<Scalar> = (0..n)
+            .map(|i| {
+                (0..rounds).fold(Scalar::ONE, |acc, j| {
+                    let bit_set = (i >> (rounds - 1 - j)) & 1 == 1;
+                    acc * if bit_set {
+                        challenges[j]
+                    } else {
+                        challenges_inv[j]
+                    }
+                })
+            })
+            .collect();
+        let s_inv: Vec<Scalar> = s.iter().rev().copied().collect();
+
+        let g_final = multiscalar_mul(&s, g);
+        let h_final = multiscalar_mul(&s_inv, h);
+
+        let mut p_final = *p;
+        for (((l, r), x), x_inv) in self
+            .l_vec
+            .iter()
+            .zip(&self.r_vec)
+            .zip(&challenges)
+            .zip(&challenges_inv)
+        {
+            p_final += l * &x.square() + r * &x_inv.square();
+        }
+
+        p_final == g_final * self.a + h_final * self.b + q * &(self.a * self.b)
+    }
+
+    pub fn serialized_len(&self) -> usize {
+        2 * self.l_vec.len() * POINT_LEN + 2 * SCALAR_LEN
+    }
+
+    /// L_0 || R_0 || ... || L_(k-1) || R_(k-1) || a || b
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_len());
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            bytes.extend_from_slice(&l.to_bytes());
+            bytes.extend_from_slice(&r.to_bytes());
+        }
+        bytes.extend_from_slice(&self.a.to_bytes());
+        bytes.extend_from_slice(&self.b.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let points_len = bytes.len().checked_sub(2 * SCALAR_LEN)?;
+        let (points_bytes, scalars_bytes) = bytes.split_at(points_len);
+        let pairs = points_bytes.chunks_exact(2 * POINT_LEN);
+        if !pairs.remainder().is_empty() {
+            return None;
+        }
+        let mut l_vec = Vec::new();
+        let mut r_vec = Vec::new();
+        for pair in pairs {
+            l_vec.push(encoding::point_from_bytes(&pair[..POINT_LEN])?);
+            r_vec.push(encoding::point_from_bytes(&pair[POINT_LEN..])?);
+        }
+        Some(Self {
+            l_vec,
+            r_vec,
+            a: encoding::scalar_from_bytes(&scalars_bytes[..SCALAR_LEN])?,
+            b: encoding::scalar_from_bytes(&scalars_bytes[SCALAR_LEN..])?,
+        })
+    }
+}
+
+pub(crate) fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(a_i, b_i)| a_i * b_i).sum()
+}
+
+/// Σ scalars_i * points_i
+pub(crate) fn multiscalar_mul(scalars: &[Scalar], points: &[ProjectivePoint]) -> ProjectivePoint {
+    scalars
+        .iter()
+        .zip(points)
+        .map(|(scalar, point)| point * scalar)
+        .sum()
+}
+
+fn fold_scalars(lo: &[Scalar], hi: &[Scalar], x_lo: &Scalar, x_hi: &Scalar) -> Vec<Scalar> {
+    lo.iter()
+        .zip(hi)
+        .map(|(lo, hi)| lo * x_lo + hi * x_hi)
+        .collect()
+}
+
+fn fold_points(
+    lo: &[ProjectivePoint],
+    hi: &[ProjectivePoint],
+    x_lo: &Scalar,
+    x_hi: &Scalar,
+) -> Vec<ProjectivePoint> {
+    lo.iter()
+        .zip(hi)
+        .map(|(lo, hi)| lo * x_lo + hi * x_hi)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::hash_to_point;
+    use k256::elliptic_curve::Field;
+    use rand::thread_rng;
+
+    fn generators(n: usize) -> (Vec<ProjectivePoint>, Vec<ProjectivePoint>, ProjectivePoint) {
+        let g = (0..n as u64)
+            .map(|i| hash_to_point(b"test-G", &i.to_le_bytes()))
+            .collect();
+        let h = (0..n as u64)
+            .map(|i| hash_to_point(b"test-H", &i.to_le_bytes()))
+            .collect();
+        (g, h, hash_to_point(b"test-Q", b""))
+    }
+
+    fn random_scalars(n: usize) -> Vec<Scalar> {
+        (0..n).map(|_| Scalar::random(&mut thread_rng())).collect()
+    }
+
+    #[test]
+    fn verify_valid_inner_product_proof_ok() {
+        for n in [1, 2, 8, 32] {
+            let (g, h, q) = generators(n);
+            let (a, b) = (random_scalars(n), random_scalars(n));
+            let p = multiscalar_mul(&a, &g) + multiscalar_mul(&b, &h) + q * inner_product(&a, &b);
+
+            let proof = InnerProductProof::prove(
+                &mut Transcript::new(b"sid", 1, b"ipp-test"),
+                &q,
+                g.clone(),
+                h.clone(),
+                a,
+                b,
+            );
+            assert!(proof.verify(&mut Transcript::new(b"sid", 1, b"ipp-test"), &q, &g, &h, &p));
+            if n > 1 {
+                // with n == 1 there is no round, so no challenge bound to the session
+                assert!(!proof.verify(
+                    &mut Transcript::new(b"sid", 2, b"ipp-test"),
+                    &q,
+                    &g,
+                    &h,
+                    &p
+                ));
+            }
+
+            let decoded = InnerProductProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(decoded, proof);
+        }
+    }
+
+    #[test]
+    fn verify_wrong_inner_product_false() {
+        let n = 8;
+        let (g, h, q) = generators(n);
+        let (a, b) = (random_scalars(n), random_scalars(n));
+        // commit to <a, b> + 1 instead of <a, b>
+        let p = multiscalar_mul(&a, &g)
+            + multiscalar_mul(&b, &h)
+            + q * (inner_product(&a, &b) + Scalar::ONE);
+
+        let proof = InnerProductProof::prove(
+            &mut Transcript::new(b"sid", 1, b"ipp-test"),
+            &q,
+            g.clone(),
+            h.clone(),
+            a,
+            b,
+        );
+        assert!(!proof.verify(&mut Transcript::new(b"sid", 1, b"ipp-test"), &q, &g, &h, &p));
+    }
+
+    #[test]
+    fn verify_too_many_rounds_false() {
+        let n = 8;
+        let (g, h, q) = generators(n);
+        let (a, b) = (random_scalars(n), random_scalars(n));
+        let p = multiscalar_mul(&a, &g) + multiscalar_mul(&b, &h) + q * inner_product(&a, &b);
+        let proof = InnerProductProof::prove(
+            &mut Transcript::new(b"sid", 1, b"ipp-test"),
+            &q,
+            g.clone(),
+            h.clone(),
+            a,
+            b,
+        );
+
+        for rounds in [4, 63, 64, 65, 200] {
+            let mut oversized = proof.clone();
+            oversized.l_vec.resize(rounds, q);
+            oversized.r_vec.resize(rounds, q);
+            let decoded = InnerProductProof::from_bytes(&oversized.to_bytes()).unwrap();
+            assert!(!decoded.verify(&mut Transcript::new(b"sid", 1, b"ipp-test"), &q, &g, &h, &p));
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! The proof system is `no_std` (it only needs `alloc`): every randomized function has a `*_with_rng` variant taking the caller's RNG.
+//! The `std` feature (enabled by default) adds convenience functions using `rand::thread_rng`.
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+use alloc::string::String;
+use encoding::POINT_LEN;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::zeroize::Zeroize;
+use k256::Scalar;
+use k256::{ProjectivePoint, U256};
+use prepared_base::BasePoint;
+use rand::{CryptoRng, Rng, RngCore};
+
+pub mod adaptor_signature;
+pub mod bip32;
+pub mod blind_schnorr;
+pub mod elgamal;
+pub mod encoding;
+pub mod frost;
+pub mod inner_product_proof;
+pub mod oprf;
+pub mod prepared_base;
+pub mod range_proof;
+pub mod ring_signature;
+pub mod schnorr;
+pub mod shuffle;
+pub mod sigma_proof;
+pub mod statement;
+pub mod storage;
+mod transcript;
+
+pub use statement::{PartyId, SessionId, Statement, Witness};
+
+/// The serde of the derives generated by [`define_proof!`], so that the crates invoking it don't need their own
+#[doc(hidden)]
+pub use serde as __serde;
+
+pub const GENERATOR: ProjectivePoint = ProjectivePoint::GENERATOR;
+
+/// Size of a [`DLogProof`] encoding: T (33 bytes) || s (32 bytes)
+pub const DLOG_PROOF_LEN: usize = POINT_LEN + encoding::SCALAR_LEN;
+
+/// Non-interactive Schnorr ZK DLOG Proof scheme with a Fiat-Shamir transformation
+/// Protocol:
+/// The prover knows a number(Scalar) x so that Y = x*G, and they want to prove they know it without revealing x
+/// The base Schnorr proof protocol works as follows:
+/// 1. Commitment: The prover generates a random number r, compute T = rG
+/// 2. Challenge: The verifier geenerates a random number c, sends it to the prover
+/// 3. Response: The prover computes s = (r + c * x) % q, sends s to the verifier
+/// 4. Verification: The verifier checks that s * G == T + (Y * c), sends true or false to the prover
+///    Indeed if y=xG then (r + cx)G == rG + cxG == T + cY
+///    Basically the prover has "hidden" the details of x by transforming both sides of the equation with an "affine" function (in the curve space): N -> T+cN
+///
+/// Making the protocol non-interactive:
+/// Instead of the verifier having to "send" the challenge c to the prover, the challenge is a deterministic, pseudo-random function of [ public problem variables + public proof ]
+/// That deterministic function can be any hash, we choose Sha256
+/// That way both the prover and verifier can derive the challenge c independently (without communicating with each other)
+///
+/// Uses curve points from secp256k1, in projective coordinates
+// RustCrypto::k256 lets us express curve points as either: Affine, Affine(compressed), Projective
+// We express point in projective coordinates so that the computation is more efficient than affine (like in the python version)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DLogProof {
+    /// T = rG, where r is a random scalar generated by the prover
+    t: ProjectivePoint,
+    /// The prover calculates s in step 3 of the protocol, based on the challenge
+    /// In the non-interactive proving system, the challenge is a deterministic number, function of the public parameters (problem + proof)
+    s: Scalar,
+}
+impl DLogProof {
+    /// Create a proof that the prover knows the witness x of the problem's statement y = x * base
+    #[cfg(feature = "std")]
+    pub fn prove_problem(problem: &Problem, witness: &Witness) -> Self {
+        Self::prove_problem_with_rng(problem, witness, &mut rand::thread_rng())
+    }
+
+    /// Same as [`DLogProof::prove_problem`], with randomness from the caller's RNG
+    pub fn prove_problem_with_rng(
+        problem: &Problem,
+        witness: &Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let Problem {
+            sid,
+            pid,
+            statement,
+        } = problem;
+        // by reference, so that no copy of the witness outlives it
+        Self::prove_secret(
+            sid.as_ref(),
+            pid.0,
+            witness.expose_secret(),
+            statement.y,
+            statement.base,
+            rng,
+        )
+    }
+
+    /// Verify the proof: check that the prover knows the witness of the problem's statement, without learning it
+    pub fn verify_problem(&self, problem: &Problem) -> bool {
+        let Problem {
+            sid,
+            pid,
+            statement,
+        } = problem;
+        self.verify(sid, pid.0, statement.y, statement.base)
+    }
+
+    // The functions below mirror the interface of the python `DLogProof` (loose sid, pid, x, y, base_point parameters),
+    // as a compatibility layer: prefer `prove_problem` and `verify_problem`, whose types can't be mixed up
+
+    /// Create a proof that the prover knows a Scalar x so that y = x*G
+    /// (G is `base_point`: usually [`GENERATOR`], but any point whose discrete log relation to y the prover knows works.
+    /// A [`PreparedBase`](prepared_base::PreparedBase) speeds up many proofs for the same base point)
+    ///
+    /// The prover knows a number(Scalar) x so that y = x*G, and they want to prove that they know it without revealing x
+    /// y is an instance variable (public, curve point, of type ProjectivePoint), x a solution (aka witness variable, known only by the prover, of type Scalar), G a constant (the generator of the curve)
+    #[cfg(feature = "std")]
+    pub fn prove(
+        sid: &(impl AsRef<[u8]> + ?Sized),
+        pid: u64,
+        x: Scalar,
+        y: ProjectivePoint,
+        base_point: impl BasePoint,
+    ) -> Self {
+        Self::prove_with_rng(sid, pid, x, y, base_point, &mut rand::thread_rng())
+    }
+
+    /// Same as [`DLogProof::prove`], with randomness from the caller's RNG
+    pub fn prove_with_rng(
+        sid: &(impl AsRef<[u8]> + ?Sized),
+        pid: u64,
+        x: Scalar,
+        y: ProjectivePoint,
+        base_point: impl BasePoint,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let mut x = x;
+        let proof = Self::prove_secret(sid.as_ref(), pid, &x, y, base_point, rng);
+        x.zeroize();
+        proof
+    }
+
+    /// The prover of [`DLogProof::prove_with_rng`], wiping its nonce r: leaking r would leak x = (s - r) / c
+    fn prove_secret(
+        sid: &[u8],
+        pid: u64,
+        x: &Scalar,
+        y: ProjectivePoint,
+        base_point: impl BasePoint,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        // r is a random Scalar
+        let mut r = Scalar::generate_vartime(rng);
+        // so t is a random curve point
+        let t = base_point.mul_scalar(&r);
+        let c = Self::calc_challenge(sid, pid, &[base_point.point(), y, t]);
+        let s = r + c * x;
+        r.zeroize();
+
+        Self { t, s }
+    }
+
+    /// Verify the proof: check that the prover knows a solution x to y = x*G, without learning x
+    pub fn verify(
+        &self,
+        sid: &(impl AsRef<[u8]> + ?Sized),
+        pid: u64,
+        y: ProjectivePoint,
+        base_point: impl BasePoint,
+    ) -> bool {
+        let sid = sid.as_ref();
+        let points_to_hash = [base_point.point(), y, self.t];
+        let c = Self::calc_challenge(sid, pid, &points_to_hash);
+
+        let lhs: ProjectivePoint = base_point.mul_scalar(&self.s);
+        let rhs = self.t + (y * c);
+        lhs == rhs
+    }
+
+    /// A proof from its commitment T and response s, e.g. decoded from another encoding
+    pub fn from_parts(t: ProjectivePoint, s: Scalar) -> Self {
+        Self { t, s }
+    }
+
+    /// The commitment T = rG
+    pub fn t(&self) -> &ProjectivePoint {
+        &self.t
+    }
+
+    /// The response s = r + c * x
+    pub fn s(&self) -> &Scalar {
+        &self.s
+    }
+
+    /// T (33 bytes, compressed SEC1) || s (32 bytes, big-endian)
+    pub fn to_bytes(&self) -> [u8; DLOG_PROOF_LEN] {
+        let mut bytes = [0u8; DLOG_PROOF_LEN];
+        bytes[..POINT_LEN].copy_from_slice(&self.t.to_bytes());
+        bytes[POINT_LEN..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != DLOG_PROOF_LEN {
+            return None;
+        }
+        Some(Self {
+            t: encoding::point_from_bytes(&bytes[..POINT_LEN])?,
+            s: encoding::scalar_from_bytes(&bytes[POINT_LEN..])?,
+        })
+    }
+
+    /// Hex of [`DLogProof::to_bytes`]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn from_hex(proof: &str) -> Option<Self> {
+        Self::from_bytes(&hex::decode(proof).ok()?)
+    }
+
+    /// Compute a hash of the public variables (from problem + proof)
+    fn hash_points(sid: &[u8], pid: u64, points: &[ProjectivePoint]) -> U256 {
+        transcript::hash_points(sid, pid, points, &[])
+    }
+
+    /// Compute the (deterministic) challenge c from the public problem variables (instance variables)
+    /// In an interactive proving system, the verifier would send the (random) challenge to the prover
+    /// Making this challenge deterministic (from a hash) helps turn the proving system into a non-interactive one
+    fn calc_challenge(sid: &[u8], pid: u64, points: &[ProjectivePoint]) -> Scalar {
+        let hash = Self::hash_points(sid, pid, points);
+        Scalar::reduce(hash)
+    }
+}
+
+/// A DLOG problem: the statement y = x * base to prove, in a session between parties
+pub struct Problem {
+    pub sid: SessionId,
+    pub pid: PartyId,
+    pub statement: Statement,
+}
+impl Problem {
+    pub fn new(sid: impl Into<SessionId>, pid: impl Into<PartyId>, statement: Statement) -> Self {
+        Self {
+            sid: sid.into(),
+            pid: pid.into(),
+            statement,
+        }
+    }
+
+    /// A problem with a random session id, party id and statement y = x * G, and its matching witness x
+    #[cfg(feature = "std")]
+    pub fn random() -> (Self, Witness) {
+        Self::random_with_rng(&mut rand::thread_rng())
+    }
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> (Self, Witness) {
+        let witness = Witness::random_with_rng(rng);
+        (Self::from_witness_with_rng(&witness, rng), witness)
+    }
+
+    /// A problem with a random session id and party id, for the statement y = x * G of the witness x
+    #[cfg(feature = "std")]
+    pub fn from_witness(witness: &Witness) -> Self {
+        Self::from_witness_with_rng(witness, &mut rand::thread_rng())
+    }
+    pub fn from_witness_with_rng(witness: &Witness, rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let sid = SessionId::random_with_rng(rng);
+        let pid = PartyId(rng.gen::<u64>());
+        Self::new(sid, pid, witness.statement(GENERATOR))
+    }
+
+    /// Same as [`Problem::from_witness`], from the secret scalar x
+    #[cfg(feature = "std")]
+    pub fn from_solution(solution_x: Scalar) -> Self {
+        Self::from_witness(&Witness::new(solution_x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::elliptic_curve::ops::MulByGenerator;
+    use rand::thread_rng;
+
+    #[test]
+    fn verify_static_valid_proof_ok() {
+        let solution_x = Scalar::generate_vartime(&mut thread_rng());
+        let Problem {
+            sid,
+            pid,
+            statement,
+        } = Problem::new(
+            "sid",
+            1,
+            Statement::new(ProjectivePoint::mul_by_generator(&solution_x)),
+        );
+        let (pid, y) = (pid.0, statement.y);
+
+        let dlog_proof = DLogProof::prove(&sid, pid, solution_x, y, GENERATOR);
+
+        let is_valid = dlog_proof.verify(&sid, pid, y, GENERATOR);
+        assert!(is_valid);
+    }
+
+    #[test]
+    fn verify_valid_proof_ok() {
+        // TODO (with more time) use deterministic pseudo-randomness based on seed for reproducibility (property-based tests)
+        for _ in 1..=10 {
+            let solution_x = Scalar::generate_vartime(&mut thread_rng());
+            let Problem {
+                sid,
+                pid,
+                statement,
+            } = Problem::from_solution(solution_x);
+            let (pid, y) = (pid.0, statement.y);
+
+            let dlog_proof = DLogProof::prove(&sid, pid, solution_x, y, GENERATOR);
+
+            let is_valid = dlog_proof.verify(&sid, pid, y, GENERATOR);
+            assert!(is_valid);
+        }
+    }
+
+    #[test]
+    fn verify_invalid_static_proof_false() {
+        let solution_x = Scalar::generate_vartime(&mut thread_rng());
+        let Problem {
+            sid,
+            pid,
+            statement,
+        } = Problem::new(
+            "sid",
+            1,
+            // y is random so doesn't verify y=x*G in the general case
+            Statement::new(ProjectivePoint::mul_by_generator(
+                &Scalar::generate_vartime(&mut thread_rng()),
+            )),
+        );
+        let (pid, y) = (pid.0, statement.y);
+
+        let dlog_proof = DLogProof::prove(&sid, pid, solution_x, y, GENERATOR);
+        let is_valid = dlog_proof.verify(&sid, pid, y, GENERATOR);
+        assert!(!is_valid);
+    }
+
+    #[test]
+    fn verify_invalid_proof_false() {
+        // TODO (with more time) use deterministic pseudo-randomness based on seed for reproducibility (property-based tests)
+        for _ in 1..=10 {
+            // problem and solution are both random so don't verify y=x*G in the general case
+            let solution_x = Scalar::generate_vartime(&mut thread_rng());
+            let (
+                Problem {
+                    sid,
+                    pid,
+                    statement,
+                },
+                _,
+            ) = Problem::random();
+            let (pid, y) = (pid.0, statement.y);
+
+            let dlog_proof = DLogProof::prove(&sid, pid, solution_x, y, GENERATOR);
+            let is_valid = dlog_proof.verify(&sid, pid, y, GENERATOR);
+            assert!(!is_valid);
+        }
+    }
+
+    #[test]
+    fn verify_proof_for_other_base_point() {
+        let base_point =
+            ProjectivePoint::mul_by_generator(&Scalar::generate_vartime(&mut thread_rng()));
+        let solution_x = Scalar::generate_vartime(&mut thread_rng());
+        let y = base_point * solution_x;
+
+        let dlog_proof = DLogProof::prove("sid", 1, solution_x, y, base_point);
+        assert!(dlog_proof.verify("sid", 1, y, base_point));
+        assert!(!dlog_proof.verify("sid", 1, y, GENERATOR));
+
+        // a prepared base point is interchangeable with the plain point
+        let prepared = prepared_base::PreparedBase::new(base_point);
+        assert!(dlog_proof.verify("sid", 1, y, &prepared));
+        let dlog_proof = DLogProof::prove("sid", 1, solution_x, y, &prepared);
+        assert!(dlog_proof.verify("sid", 1, y, base_point));
+    }
+
+    #[test]
+    fn verify_problem_valid_proof_ok() {
+        let (problem, witness) = Problem::random();
+
+        let dlog_proof = DLogProof::prove_problem(&problem, &witness);
+
+        assert!(dlog_proof.verify_problem(&problem));
+        // same proof as with the compatibility layer
+        assert!(dlog_proof.verify(
+            &problem.sid,
+            problem.pid.0,
+            problem.statement.y,
+            problem.statement.base
+        ));
+    }
+
+    #[test]
+    fn verify_problem_is_bound_to_session_party_and_statement() {
+        let (problem, witness) = Problem::random();
+        let dlog_proof = DLogProof::prove_problem(&problem, &witness);
+
+        let other_session = Problem::new("other sid", problem.pid, problem.statement);
+        let other_party = Problem::new(problem.sid.clone(), problem.pid.0 ^ 1, problem.statement);
+        let (other_statement, _) = Problem::random();
+        let other_statement =
+            Problem::new(problem.sid.clone(), problem.pid, other_statement.statement);
+        for other in [other_session, other_party, other_statement] {
+            assert!(!dlog_proof.verify_problem(&other));
+        }
+    }
+
+    #[test]
+    fn verify_problem_with_other_base_point() {
+        let base = ProjectivePoint::mul_by_generator(&Scalar::generate_vartime(&mut thread_rng()));
+        let witness = Witness::random_with_rng(&mut thread_rng());
+        let problem = Problem::new("sid", 1, witness.statement(base));
+
+        let dlog_proof = DLogProof::prove_problem(&problem, &witness);
+
+        assert!(dlog_proof.verify_problem(&problem));
+        assert!(!dlog_proof.verify_problem(&Problem::new(
+            "sid",
+            1,
+            Statement::new(problem.statement.y)
+        )));
+    }
+}
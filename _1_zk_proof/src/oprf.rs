@@ -0,0 +1,439 @@
+//! Verifiable oblivious pseudorandom function (VOPRF): the protocol of RFC 9497 in verifiable mode, on secp256k1
+//!
+//! The server holds a key k (public key Y = k*G), and the client an input x, e.g. a password. The client learns F(k, x) = H(x, k * H1(x)),
+//! the server learns nothing about x, and the client is convinced that the server used the key behind Y:
+//! 1. Blind: the client hashes its input to the curve, P = H1(x), and sends B = r * P for a random blind r
+//! 2. BlindEvaluate: the server sends Z = k * B, and a DLEQ proof that log_G(Y) = log_B(Z)
+//! 3. Finalize: the client verifies the proof, unblinds N = r^-1 * Z = k * P and outputs H(x, N)
+//!
+//! A batch of blinded elements B_i is evaluated with a single proof, for their composite M = Σ d_i * B_i and Z = Σ d_i * Z_i,
+//! with weights d_i hashed from the whole batch (RFC 9497's ComputeComposites).
+//!
+//! RFC 9497 doesn't define a secp256k1 ciphersuite: this one hashes to the curve with secp256k1_XMD:SHA-256_SSWU_RO_ and SHA-256,
+//! and its DLEQ proof follows the Fiat-Shamir conventions of `DLogProof` (sid, pid of the server) instead of the RFC's transcript,
+//! so its outputs and proofs are not interoperable with implementations of the RFC's ciphersuites.
+use crate::encoding;
+use crate::transcript;
+use crate::GENERATOR;
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::elliptic_curve::zeroize::Zeroize;
+use k256::elliptic_curve::Field;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// "OPRFV1-" || mode (0x01: VOPRF) || "-" || ciphersuite identifier
+const CONTEXT_STRING: &[u8] = b"OPRFV1-\x01-secp256k1-SHA256";
+const HASH_TO_GROUP_DST: &[u8] = b"HashToGroup-OPRFV1-\x01-secp256k1-SHA256";
+const COMPOSITE_LABEL: &[u8] = b"Composite";
+
+crate::define_proof! {
+    /// The server used the same key k for its public key and the (composite) evaluation: Y = k*G and Z = k*M
+    dleq, "oprf-dleq", (k), (Y, Z), (G, M) : Y = (k * G), Z = (k * M)
+}
+
+/// B = r * H1(x), sent by the client to the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindedElement(#[serde(with = "encoding::serde_point")] ProjectivePoint);
+
+/// Z = k * B, sent back by the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluatedElement(#[serde(with = "encoding::serde_point")] ProjectivePoint);
+
+/// Proof that a batch of evaluated elements were computed with the server's key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluationProof(dleq::Proof);
+
+/// What the client keeps between blinding and finalizing: its input and blind r
+///
+/// They are wiped from memory when dropped
+pub struct BlindState {
+    input: Vec<u8>,
+    blind: Scalar,
+    blinded_element: BlindedElement,
+}
+impl BlindState {
+    pub fn blinded_element(&self) -> &BlindedElement {
+        &self.blinded_element
+    }
+}
+impl Drop for BlindState {
+    fn drop(&mut self) {
+        self.input.zeroize();
+        self.blind.zeroize();
+    }
+}
+
+/// Server side: evaluates the blinded elements with its key
+pub struct OprfServer {
+    key: Scalar,
+    public_key: ProjectivePoint,
+}
+impl OprfServer {
+    /// A server with a random key
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        Self::new_with_rng(&mut rand::thread_rng())
+    }
+    pub fn new_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        Self::from_key(Scalar::random(rng)).expect("a random scalar is non-zero")
+    }
+
+    /// Fails for the zero key
+    pub fn from_key(key: Scalar) -> Result<Self, OprfError> {
+        if key == Scalar::ZERO {
+            return Err(OprfError::InvalidKey);
+        }
+        Ok(Self {
+            key,
+            public_key: ProjectivePoint::mul_by_generator(&key),
+        })
+    }
+
+    /// Y = k*G, to give to the clients
+    pub fn public_key(&self) -> &ProjectivePoint {
+        &self.public_key
+    }
+
+    /// Evaluate a batch of blinded elements, with one proof for the whole batch
+    #[cfg(feature = "std")]
+    pub fn blind_evaluate(
+        &self,
+        sid: &str,
+        pid: u64,
+        blinded_elements: &[BlindedElement],
+    ) -> Result<(Vec<EvaluatedElement>, EvaluationProof), OprfError> {
+        self.blind_evaluate_with_rng(sid, pid, blinded_elements, &mut rand::thread_rng())
+    }
+
+    pub fn blind_evaluate_with_rng(
+        &self,
+        sid: &str,
+        pid: u64,
+        blinded_elements: &[BlindedElement],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Vec<EvaluatedElement>, EvaluationProof), OprfError> {
+        if blinded_elements.is_empty() {
+            return Err(OprfError::EmptyBatch);
+        }
+        let evaluated_elements: Vec<EvaluatedElement> = blinded_elements
+            .iter()
+            .map(|blinded| EvaluatedElement(blinded.0 * self.key))
+            .collect();
+
+        let (m, z) = composites(
+            sid,
+            pid,
+            &self.public_key,
+            blinded_elements,
+            &evaluated_elements,
+        );
+        let assignments = dleq::ProveAssignments {
+            k: &self.key,
+            Y: &self.public_key,
+            Z: &z,
+            G: &GENERATOR,
+            M: &m,
+        };
+        let proof = dleq::prove_with_rng(sid, pid, assignments, rng);
+        Ok((evaluated_elements, EvaluationProof(proof)))
+    }
+
+    /// F(k, x) computed directly from the input, e.g. to store the outputs of a password database
+    pub fn evaluate(&self, input: &[u8]) -> Result<[u8; 32], OprfError> {
+        let point = hash_to_group(input)?;
+        finalize_hash(input, &(point * self.key))
+    }
+}
+#[cfg(feature = "std")]
+impl Default for OprfServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Drop for OprfServer {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+/// Client side: blinds its inputs, and checks and unblinds the server's evaluations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OprfClient {
+    server_public_key: ProjectivePoint,
+}
+impl OprfClient {
+    pub fn new(server_public_key: ProjectivePoint) -> Self {
+        Self { server_public_key }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn blind(&self, input: &[u8]) -> Result<BlindState, OprfError> {
+        self.blind_with_rng(input, &mut rand::thread_rng())
+    }
+
+    /// B = r * H1(x): send [`BlindState::blinded_element`] to the server. Inputs are at most 65535 bytes long
+    pub fn blind_with_rng(
+        &self,
+        input: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<BlindState, OprfError> {
+        let blind = Scalar::random(rng);
+        let blinded_element = BlindedElement(hash_to_group(input)? * blind);
+        Ok(BlindState {
+            input: input.to_vec(),
+            blind,
+            blinded_element,
+        })
+    }
+
+    /// Check the server's proof for the whole batch, then output F(k, x) for every input, in the order of `states`
+    pub fn finalize(
+        &self,
+        sid: &str,
+        pid: u64,
+        states: &[BlindState],
+        evaluated_elements: &[EvaluatedElement],
+        proof: &EvaluationProof,
+    ) -> Result<Vec<[u8; 32]>, OprfError> {
+        if states.is_empty() {
+            return Err(OprfError::EmptyBatch);
+        }
+        if states.len() != evaluated_elements.len() {
+            return Err(OprfError::LengthMismatch);
+        }
+        let blinded_elements: Vec<BlindedElement> =
+            states.iter().map(|state| state.blinded_element).collect();
+        let (m, z) = composites(
+            sid,
+            pid,
+            &self.server_public_key,
+            &blinded_elements,
+            evaluated_elements,
+        );
+        let assignments = dleq::VerifyAssignments {
+            Y: &self.server_public_key,
+            Z: &z,
+            G: &GENERATOR,
+            M: &m,
+        };
+        if !dleq::verify(&proof.0, sid, pid, assignments) {
+            return Err(OprfError::InvalidProof);
+        }
+
+        states
+            .iter()
+            .zip(evaluated_elements)
+            .map(|(state, evaluated)| {
+                let blind_inverse = state.blind.invert().expect("the blind is non-zero");
+                finalize_hash(&state.input, &(evaluated.0 * blind_inverse))
+            })
+            .collect()
+    }
+}
+
+/// H1(x): the input hashed to the curve
+fn hash_to_group(input: &[u8]) -> Result<ProjectivePoint, OprfError> {
+    if input.len() > u16::MAX as usize {
+        return Err(OprfError::InputTooLong);
+    }
+    let point = transcript::hash_to_point(HASH_TO_GROUP_DST, input);
+    // happens with negligible probability
+    if point == ProjectivePoint::IDENTITY {
+        return Err(OprfError::InvalidInput);
+    }
+    Ok(point)
+}
+
+/// F(k, x) = SHA-256(len(x) || x || len(N) || N || "Finalize"), lengths as 2-byte big-endian, N compressed
+fn finalize_hash(input: &[u8], unblinded: &ProjectivePoint) -> Result<[u8; 32], OprfError> {
+    let input_len = u16::try_from(input.len()).map_err(|_| OprfError::InputTooLong)?;
+    let element = unblinded.to_bytes();
+    Ok(Sha256::new()
+        .chain_update(input_len.to_be_bytes())
+        .chain_update(input)
+        .chain_update((element.len() as u16).to_be_bytes())
+        .chain_update(element)
+        .chain_update(b"Finalize")
+        .finalize()
+        .into())
+}
+
+/// M = Σ d_i * B_i and Z = Σ d_i * Z_i, with d_i hashed from a challenge over the public key and the whole batch
+fn composites(
+    sid: &str,
+    pid: u64,
+    public_key: &ProjectivePoint,
+    blinded_elements: &[BlindedElement],
+    evaluated_elements: &[EvaluatedElement],
+) -> (ProjectivePoint, ProjectivePoint) {
+    let mut points = alloc::vec![*public_key];
+    points.extend(blinded_elements.iter().map(|b| b.0));
+    points.extend(evaluated_elements.iter().map(|z| z.0));
+    let seed = transcript::challenge(sid.as_bytes(), pid, &points, CONTEXT_STRING).to_bytes();
+
+    let mut m = ProjectivePoint::IDENTITY;
+    let mut z = ProjectivePoint::IDENTITY;
+    for (i, (blinded, evaluated)) in blinded_elements.iter().zip(evaluated_elements).enumerate() {
+        let msg = [COMPOSITE_LABEL, &seed, &(i as u64).to_le_bytes()].concat();
+        let d_i = transcript::challenge(sid.as_bytes(), pid, &[], &msg);
+        m += blinded.0 * d_i;
+        z += evaluated.0 * d_i;
+    }
+    (m, z)
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum OprfError {
+    #[error("the server key must be non-zero")]
+    InvalidKey,
+    #[error("the input hashes to the identity point")]
+    InvalidInput,
+    #[error("the input must be at most 65535 bytes long")]
+    InputTooLong,
+    #[error("there must be at least one element to evaluate")]
+    EmptyBatch,
+    #[error("there must be exactly one evaluated element per blinded element")]
+    LengthMismatch,
+    #[error("the evaluation proof is invalid")]
+    InvalidProof,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn oblivious_evaluation_matches_direct_evaluation() {
+        let server = OprfServer::new();
+        let client = OprfClient::new(*server.public_key());
+
+        let inputs: [&[u8]; 3] = [b"hunter2", b"correct horse battery staple", b""];
+        let states: Vec<BlindState> = inputs
+            .iter()
+            .map(|input| client.blind(input).unwrap())
+            .collect();
+        let blinded: Vec<BlindedElement> = states
+            .iter()
+            .map(|state| *state.blinded_element())
+            .collect();
+        let (evaluated, proof) = server.blind_evaluate("sid", 1, &blinded).unwrap();
+        let outputs = client
+            .finalize("sid", 1, &states, &evaluated, &proof)
+            .unwrap();
+
+        for (input, output) in inputs.iter().zip(&outputs) {
+            assert_eq!(*output, server.evaluate(input).unwrap());
+        }
+        assert_ne!(outputs[0], outputs[1]);
+
+        // a fresh blind hides the input, but gives the same output
+        let state = client.blind(inputs[0]).unwrap();
+        assert_ne!(*state.blinded_element(), blinded[0]);
+        let (evaluated, proof) = server
+            .blind_evaluate("sid", 1, &[*state.blinded_element()])
+            .unwrap();
+        assert_eq!(
+            client
+                .finalize("sid", 1, &[state], &evaluated, &proof)
+                .unwrap(),
+            [outputs[0]]
+        );
+    }
+
+    #[test]
+    fn evaluation_with_another_key_is_rejected() {
+        let server = OprfServer::new();
+        let client = OprfClient::new(*server.public_key());
+        let states = [client.blind(b"a").unwrap(), client.blind(b"b").unwrap()];
+        let blinded = [*states[0].blinded_element(), *states[1].blinded_element()];
+        let (evaluated, proof) = server.blind_evaluate("sid", 1, &blinded).unwrap();
+        assert!(client
+            .finalize("sid", 1, &states, &evaluated, &proof)
+            .is_ok());
+
+        // another key, a tampered element, another session or server
+        let other_server = OprfServer::new();
+        let (other_evaluated, other_proof) =
+            other_server.blind_evaluate("sid", 1, &blinded).unwrap();
+        assert_eq!(
+            client.finalize("sid", 1, &states, &other_evaluated, &other_proof),
+            Err(OprfError::InvalidProof)
+        );
+        let mut tampered = evaluated.clone();
+        tampered[1] = other_evaluated[1];
+        assert_eq!(
+            client.finalize("sid", 1, &states, &tampered, &proof),
+            Err(OprfError::InvalidProof)
+        );
+        assert_eq!(
+            client.finalize("other sid", 1, &states, &evaluated, &proof),
+            Err(OprfError::InvalidProof)
+        );
+        assert_eq!(
+            client.finalize("sid", 2, &states, &evaluated, &proof),
+            Err(OprfError::InvalidProof)
+        );
+        assert_eq!(
+            client.finalize("sid", 1, &states, &evaluated[..1], &proof),
+            Err(OprfError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn messages_serde_roundtrip() {
+        let server = OprfServer::new();
+        let client = OprfClient::new(*server.public_key());
+        let state = client.blind(b"password").unwrap();
+        let blinded: BlindedElement =
+            serde_json::from_str(&serde_json::to_string(state.blinded_element()).unwrap()).unwrap();
+        let (evaluated, proof) = server.blind_evaluate("sid", 1, &[blinded]).unwrap();
+        let json = serde_json::to_string(&(&evaluated, &proof)).unwrap();
+        let (evaluated, proof): (Vec<EvaluatedElement>, EvaluationProof) =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            client
+                .finalize("sid", 1, &[state], &evaluated, &proof)
+                .unwrap(),
+            [server.evaluate(b"password").unwrap()]
+        );
+        assert_eq!(
+            OprfServer::from_key(Scalar::ZERO).err(),
+            Some(OprfError::InvalidKey)
+        );
+        assert_eq!(
+            server.blind_evaluate("sid", 1, &[]).err(),
+            Some(OprfError::EmptyBatch)
+        );
+    }
+
+    #[test]
+    fn identity_elements_are_rejected_by_serde() {
+        let identity = serde_json::to_string(&k256::AffinePoint::IDENTITY).unwrap();
+        assert!(serde_json::from_str::<BlindedElement>(&identity).is_err());
+        assert!(serde_json::from_str::<EvaluatedElement>(&identity).is_err());
+    }
+
+    #[test]
+    fn inputs_longer_than_their_length_prefix_are_rejected() {
+        let server = OprfServer::new();
+        let client = OprfClient::new(*server.public_key());
+        let longest = alloc::vec![7u8; u16::MAX as usize];
+        let too_long = alloc::vec![7u8; u16::MAX as usize + 1];
+
+        assert!(server.evaluate(&longest).is_ok());
+        assert!(client.blind_with_rng(&longest, &mut thread_rng()).is_ok());
+        assert_eq!(
+            server.evaluate(&too_long).err(),
+            Some(OprfError::InputTooLong)
+        );
+        assert_eq!(
+            client.blind_with_rng(&too_long, &mut thread_rng()).err(),
+            Some(OprfError::InputTooLong)
+        );
+    }
+}
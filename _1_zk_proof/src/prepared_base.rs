@@ -0,0 +1,145 @@
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::Curve;
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::elliptic_curve::subtle::{ConditionallySelectable, ConstantTimeEq};
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+
+/// Width in bits of the windows of a [`PreparedBase`]: 256 / 4 = 64 windows of 16 multiples
+const WINDOW_BITS: usize = 4;
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+const NUM_WINDOWS: usize = 256 / WINDOW_BITS;
+
+/// A base point that the proofs multiply by scalars: `prove`, `verify` and `batch_verify` accept any of
+/// - `ProjectivePoint`: variable-base multiplication (or k256's precomputed table when the point is the generator G)
+/// - [`PreparedBase`]: a precomputed table, for a base point other than G that is reused across many proofs (e.g. Pedersen `H`)
+pub trait BasePoint {
+    fn point(&self) -> ProjectivePoint;
+
+    /// k * self
+    fn mul_scalar(&self, k: &Scalar) -> ProjectivePoint;
+}
+
+impl BasePoint for ProjectivePoint {
+    fn point(&self) -> ProjectivePoint {
+        *self
+    }
+
+    fn mul_scalar(&self, k: &Scalar) -> ProjectivePoint {
+        if *self == ProjectivePoint::GENERATOR {
+            ProjectivePoint::mul_by_generator(k)
+        } else {
+            self * k
+        }
+    }
+}
+
+impl<T: BasePoint + ?Sized> BasePoint for &T {
+    fn point(&self) -> ProjectivePoint {
+        (**self).point()
+    }
+
+    fn mul_scalar(&self, k: &Scalar) -> ProjectivePoint {
+        (**self).mul_scalar(k)
+    }
+}
+
+/// A base point P with a precomputed fixed-base table, computed once and reused for every multiplication by P
+///
+/// The table holds j * 16^i * P for every 4-bit window i and digit j, so k * P = Σ_i table[i][k_i] where k_i are the base 16 digits of k:
+/// 64 point additions, instead of ~256 doublings and additions for a variable-base multiplication.
+/// The table entry of each window is selected in constant time, so secret scalars (the prover's nonces) can be multiplied too.
+///
+/// Building the table costs about as much as a dozen variable-base multiplications, and it takes ~90 KB: worth it for a base reused in dozens of proofs
+#[derive(Debug, Clone)]
+pub struct PreparedBase {
+    point: ProjectivePoint,
+    table: Vec<[AffinePoint; WINDOW_SIZE]>,
+}
+impl PreparedBase {
+    pub fn new(point: ProjectivePoint) -> Self {
+        let mut multiples = Vec::with_capacity(NUM_WINDOWS * WINDOW_SIZE);
+        // 16^i * P
+        let mut window_base = point;
+        for _ in 0..NUM_WINDOWS {
+            let mut multiple = ProjectivePoint::IDENTITY;
+            for _ in 0..WINDOW_SIZE {
+                multiples.push(multiple);
+                multiple += window_base;
+            }
+            window_base = multiple;
+        }
+
+        // a single batch normalization of the whole table, sharing one field inversion
+        let mut affine = alloc::vec![AffinePoint::IDENTITY; multiples.len()];
+        ProjectivePoint::batch_normalize(&multiples, &mut affine);
+        let table = affine
+            .chunks_exact(WINDOW_SIZE)
+            .map(|window| window.try_into().expect("chunks of WINDOW_SIZE"))
+            .collect();
+        Self { point, table }
+    }
+}
+
+impl BasePoint for PreparedBase {
+    fn point(&self) -> ProjectivePoint {
+        self.point
+    }
+
+    fn mul_scalar(&self, k: &Scalar) -> ProjectivePoint {
+        // lookup of the multiple digit * 16^i * P, reading every entry so that the memory accesses don't depend on the digit
+        let select = |window: &[AffinePoint; WINDOW_SIZE], digit: u8| {
+            let mut selected = AffinePoint::IDENTITY;
+            for (j, multiple) in window.iter().enumerate() {
+                selected.conditional_assign(multiple, (j as u8).ct_eq(&digit));
+            }
+            selected
+        };
+
+        // big-endian bytes, each holding 2 windows: iterate from the least significant byte to match the table order
+        let bytes = k.to_bytes();
+        let mut result = ProjectivePoint::IDENTITY;
+        for (byte, windows) in bytes.iter().rev().zip(self.table.chunks_exact(2)) {
+            result += select(&windows[0], byte & 0x0f);
+            result += select(&windows[1], byte >> 4);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::elliptic_curve::Field;
+    use rand::thread_rng;
+
+    #[test]
+    fn prepared_base_mul_matches_variable_base_mul() {
+        let point = ProjectivePoint::mul_by_generator(&Scalar::random(&mut thread_rng()));
+        let prepared = PreparedBase::new(point);
+
+        let scalars = [
+            Scalar::ZERO,
+            Scalar::ONE,
+            -Scalar::ONE,
+            Scalar::from(16u64),
+            Scalar::random(&mut thread_rng()),
+        ];
+        for k in scalars {
+            assert_eq!(prepared.mul_scalar(&k), point * k);
+        }
+        assert_eq!(prepared.point(), point);
+    }
+
+    #[test]
+    fn generator_base_point_uses_generator_table() {
+        let k = Scalar::random(&mut thread_rng());
+        assert_eq!(
+            ProjectivePoint::GENERATOR.mul_scalar(&k),
+            ProjectivePoint::GENERATOR * k
+        );
+        assert_eq!(
+            PreparedBase::new(ProjectivePoint::GENERATOR).mul_scalar(&k),
+            ProjectivePoint::mul_by_generator(&k)
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use crate::encoding::{self, POINT_LEN, SCALAR_LEN};
+use crate::inner_product_proof::{inner_product, multiscalar_mul, InnerProductProof};
+use crate::transcript::{self, Transcript};
+use crate::GENERATOR;
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::Field;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Number of bits of the proven range: values are in [0, 2^64)
+pub const RANGE_BITS: usize = 64;
+
+const TRANSCRIPT_LABEL: &[u8] = b"_1_zk_proof-bulletproofs-range-proof-v1";
+
+/// Generators of Pedersen commitments V = v*B + gamma*B_blinding
+///
+/// B is the curve generator, B_blinding is hashed to the curve so that nobody knows its discrete log w.r.t. B (which would let them open V to any value)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PedersenGens {
+    pub b: ProjectivePoint,
+    pub b_blinding: ProjectivePoint,
+}
+impl Default for PedersenGens {
+    fn default() -> Self {
+        Self {
+            b: GENERATOR,
+            b_blinding: transcript::hash_to_point(
+                b"_1_zk_proof-pedersen-blinding-generator-v1",
+                b"",
+            ),
+        }
+    }
+}
+impl PedersenGens {
+    pub fn commit(&self, value: Scalar, blinding: Scalar) -> ProjectivePoint {
+        self.b * value + self.b_blinding * blinding
+    }
+}
+
+/// The vectors of generators G_i, H_i used by the range proof's vector commitments, hashed to the curve
+///
+/// Computing them is expensive: create them once (with the capacity of the biggest aggregated proof) and reuse them
+#[derive(Debug, Clone)]
+pub struct BulletproofGens {
+    g_vec: Vec<ProjectivePoint>,
+    h_vec: Vec<ProjectivePoint>,
+}
+impl BulletproofGens {
+    /// Generators for aggregated proofs of up to `party_capacity` values
+    pub fn new(party_capacity: usize) -> Self {
+        let capacity = (party_capacity * RANGE_BITS) as u64;
+        let hash_gens = |dst: &[u8]| {
+            (0..capacity)
+                .map(|i| transcript::hash_to_point(dst, &i.to_le_bytes()))
+                .collect()
+        };
+        Self {
+            g_vec: hash_gens(b"_1_zk_proof-bulletproofs-G-v1"),
+            h_vec: hash_gens(b"_1_zk_proof-bulletproofs-H-v1"),
+        }
+    }
+
+    pub fn party_capacity(&self) -> usize {
+        self.g_vec.len() / RANGE_BITS
+    }
+}
+
+/// Bulletproofs range proof (Bünz et al. 2017, section 4): proves that Pedersen commitments V_j = v_j*B + gamma_j*B_blinding hide values v_j in [0, 2^64),
+/// without revealing them. m values are proven at once (aggregation) for a proof of size O(log2(64 * m)).
+///
+/// Protocol (n = 64 bits per value, N = n*m):
+/// 1. Commitment to the bits: a_L are the bits of all the v_j, a_R = a_L - 1, s_L and s_R are random blinding vectors.
+///    The prover sends A = alpha*B_blinding + <a_L, G> + <a_R, H> and S = rho*B_blinding + <s_L, G> + <s_R, H>
+/// 2. Challenge: y, z = H(transcript)
+/// 3. Commitment to the polynomial t(X) = <l(X), r(X)>, where l(X) = a_L - z + s_L*X and r(X) = y^N ∘ (a_R + z + s_R*X) + Σ_j z^(2+j) * 2^n (shifted to the j-th block).
+///    Its constant term t0 is Σ_j z^(2+j)*v_j + δ(y, z) if and only if the a_L are bits and they add up to the v_j.
+///    The prover sends T1 = t1*B + tau1*B_blinding and T2 = t2*B + tau2*B_blinding
+/// 4. Challenge: x = H(transcript)
+/// 5. Response: the prover evaluates at x: t_hat = t(x), tau_x (blinding of t_hat), mu (blinding of A + xS), and l = l(x), r = r(x)
+/// 6. Verification:
+///    - t_hat*B + tau_x*B_blinding == Σ_j z^(2+j)*V_j + δ(y, z)*B + x*T1 + x^2*T2
+///    - l and r are correct and t_hat = <l, r>, which is proven with an `InnerProductProof` instead of sending l and r (O(log N) instead of O(N)),
+///      with generators G and H' = y^-i * H_i, and Q = w*B where w = H(transcript)
+///
+/// Challenges use the same Fiat-Shamir conventions as `DLogProof`: Sha256 of (sid, pid, points, ...) reduced modulo the curve order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProof {
+    #[serde(with = "encoding::serde_point")]
+    a: ProjectivePoint,
+    #[serde(with = "encoding::serde_point")]
+    s: ProjectivePoint,
+    #[serde(with = "encoding::serde_point")]
+    t1: ProjectivePoint,
+    #[serde(with = "encoding::serde_point")]
+    t2: ProjectivePoint,
+    tau_x: Scalar,
+    mu: Scalar,
+    t_hat: Scalar,
+    ipp: InnerProductProof,
+}
+impl RangeProof {
+    /// Prove that one value is in [0, 2^64), and get its Pedersen commitment
+    #[cfg(feature = "std")]
+    pub fn prove_single(
+        sid: &str,
+        pid: u64,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        value: u64,
+        blinding: Scalar,
+    ) -> Result<(Self, ProjectivePoint), RangeProofError> {
+        Self::prove_single_with_rng(
+            sid,
+            pid,
+            bp_gens,
+            pc_gens,
+            value,
+            blinding,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Same as [`RangeProof::prove_single`], with randomness from the caller's RNG
+    pub fn prove_single_with_rng(
+        sid: &str,
+        pid: u64,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        value: u64,
+        blinding: Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self, ProjectivePoint), RangeProofError> {
+        let (proof, commitments) =
+            Self::prove_multiple_with_rng(sid, pid, bp_gens, pc_gens, &[value], &[blinding], rng)?;
+        Ok((proof, commitments[0]))
+    }
+
+    /// Prove that all the values are in [0, 2^64) with one aggregated proof, and get their Pedersen commitments
+    ///
+    /// The number of values must be a power of 2
+    #[cfg(feature = "std")]
+    pub fn prove_multiple(
+        sid: &str,
+        pid: u64,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        values: &[u64],
+        blindings: &[Scalar],
+    ) -> Result<(Self, Vec<ProjectivePoint>), RangeProofError> {
+        Self::prove_multiple_with_rng(
+            sid,
+            pid,
+            bp_gens,
+            pc_gens,
+            values,
+            blindings,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Same as [`RangeProof::prove_multiple`], with randomness from the caller's RNG
+    pub fn prove_multiple_with_rng(
+        sid: &str,
+        pid: u64,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        values: &[u64],
+        blindings: &[Scalar],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self, Vec<ProjectivePoint>), RangeProofError> {
+        let m = values.len();
+        if values.len() != blindings.len() {
+            return Err(RangeProofError::WrongNumBlindings);
+        }
+        check_aggregation_size(m, bp_gens)?;
+        let n = RANGE_BITS;
+        let nm = n * m;
+        let g = &bp_gens.g_vec[..nm];
+        let h = &bp_gens.h_vec[..nm];
+
+        let commitments: Vec<ProjectivePoint> = values
+            .iter()
+            .zip(blindings)
+            .map(|(v, gamma)| pc_gens.commit(Scalar::from(*v), *gamma))
+            .collect();
+        let mut transcript = Transcript::new(sid.as_bytes(), pid, TRANSCRIPT_LABEL);
+        transcript.append_u64(n as u64);
+        transcript.append_u64(m as u64);
+        for commitment in &commitments {
+            transcript.append_point(commitment);
+        }
+
+        // 1. commitment to the bits
+        let a_l: Vec<Scalar> = values
+            .iter()
+            .flat_map(|v| (0..n).map(move |k| Scalar::from((v >> k) & 1)))
+            .collect();
+        let a_r: Vec<Scalar> = a_l.iter().map(|bit| bit - &Scalar::ONE).collect();
+        let alpha = Scalar::random(&mut *rng);
+        let a = pc_gens.b_blinding * alpha + multiscalar_mul(&a_l, g) + multiscalar_mul(&a_r, h);
+
+        let s_l: Vec<Scalar> = (0..nm).map(|_| Scalar::random(&mut *rng)).collect();
+        let s_r: Vec<Scalar> = (0..nm).map(|_| Scalar::random(&mut *rng)).collect();
+        let rho = Scalar::random(&mut *rng);
+        let s = pc_gens.b_blinding * rho + multiscalar_mul(&s_l, g) + multiscalar_mul(&s_r, h);
+
+        // 2. challenges y, z
+        transcript.append_point(&a);
+        transcript.append_point(&s);
+        let y = transcript.challenge();
+        let z = transcript.challenge();
+
+        // 3. commitment to t(X) = <l(X), r(X)> = t0 + t1*X + t2*X^2
+        let y_powers = powers(&y, nm);
+        let z_2_powers = powers(&z, m)
+            .iter()
+            .map(|z_j| z_j * &z.square())
+            .collect::<Vec<_>>();
+        let two_powers = powers(&Scalar::from(2u64), n);
+
+        let l0: Vec<Scalar> = a_l.iter().map(|a_l_i| a_l_i - &z).collect();
+        let l1 = s_l;
+        let r0: Vec<Scalar> = (0..nm)
+            .map(|i| y_powers[i] * (a_r[i] + z) + z_2_powers[i / n] * two_powers[i % n])
+            .collect();
+        let r1: Vec<Scalar> = (0..nm).map(|i| y_powers[i] * s_r[i]).collect();
+
+        let t1 = inner_product(&l0, &r1) + inner_product(&l1, &r0);
+        let t2 = inner_product(&l1, &r1);
+        let tau1 = Scalar::random(&mut *rng);
+        let tau2 = Scalar::random(&mut *rng);
+        let big_t1 = pc_gens.commit(t1, tau1);
+        let big_t2 = pc_gens.commit(t2, tau2);
+
+        // 4. challenge x
+        transcript.append_point(&big_t1);
+        transcript.append_point(&big_t2);
+        let x = transcript.challenge();
+
+        // 5. evaluate at x
+        let tau_x = tau2 * x.square()
+            + tau1 * x
+            + z_2_powers
+                .iter()
+                .zip(blindings)
+                .map(|(z_j, gamma)| z_j * gamma)
+                .sum::<Scalar>();
+        let mu = alpha + rho * x;
+        let l: Vec<Scalar> = l0.iter().zip(&l1).map(|(l0, l1)| l0 + l1 * &x).collect();
+        let r: Vec<Scalar> = r0.iter().zip(&r1).map(|(r0, r1)| r0 + r1 * &x).collect();
+        let t_hat = inner_product(&l, &r);
+
+        transcript.append_scalar(&tau_x);
+        transcript.append_scalar(&mu);
+        transcript.append_scalar(&t_hat);
+        let w = transcript.challenge();
+        let q = pc_gens.b * w;
+
+        let h_prime = h_prime(h, &y);
+        let ipp = InnerProductProof::prove(&mut transcript, &q, g.to_vec(), h_prime, l, r);
+
+        let proof = Self {
+            a,
+            s,
+            t1: big_t1,
+            t2: big_t2,
+            tau_x,
+            mu,
+            t_hat,
+            ipp,
+        };
+        Ok((proof, commitments))
+    }
+
+    /// Verify that every commitment hides a value in [0, 2^64)
+    pub fn verify(
+        &self,
+        sid: &str,
+        pid: u64,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        commitments: &[ProjectivePoint],
+    ) -> bool {
+        let m = commitments.len();
+        if check_aggregation_size(m, bp_gens).is_err() {
+            return false;
+        }
+        let n = RANGE_BITS;
+        let nm = n * m;
+        let g = &bp_gens.g_vec[..nm];
+        let h = &bp_gens.h_vec[..nm];
+
+        let mut transcript = Transcript::new(sid.as_bytes(), pid, TRANSCRIPT_LABEL);
+        transcript.append_u64(n as u64);
+        transcript.append_u64(m as u64);
+        for commitment in commitments {
+            transcript.append_point(commitment);
+        }
+        transcript.append_point(&self.a);
+        transcript.append_point(&self.s);
+        let y = transcript.challenge();
+        let z = transcript.challenge();
+        transcript.append_point(&self.t1);
+        transcript.append_point(&self.t2);
+        let x = transcript.challenge();
+        transcript.append_scalar(&self.tau_x);
+        transcript.append_scalar(&self.mu);
+        transcript.append_scalar(&self.t_hat);
+        let w = transcript.challenge();
+        let q = pc_gens.b * w;
+
+        let y_powers = powers(&y, nm);
+        let z_2_powers = powers(&z, m)
+            .iter()
+            .map(|z_j| z_j * &z.square())
+            .collect::<Vec<_>>();
+        let two_powers = powers(&Scalar::from(2u64), n);
+
+        // t_hat*B + tau_x*B_blinding == Σ_j z^(2+j)*V_j + δ(y, z)*B + x*T1 + x^2*T2
+        // where δ(y, z) = (z - z^2) * <1, y^N> - Σ_j z^(3+j) * <1, 2^n>
+        let sum_y_powers: Scalar = y_powers.iter().sum();
+        let sum_two_powers = Scalar::from(u64::MAX);
+        let delta = (z - z.square()) * sum_y_powers
+            - z_2_powers.iter().map(|z_j| z_j * &z).sum::<Scalar>() * sum_two_powers;
+        let lhs = pc_gens.commit(self.t_hat, self.tau_x);
+        let rhs = multiscalar_mul(&z_2_powers, commitments)
+            + pc_gens.b * delta
+            + self.t1 * x
+            + self.t2 * x.square();
+        if lhs != rhs {
+            return false;
+        }
+
+        // P = A + x*S - z*<1, G> + <z*y^N + Σ_j z^(2+j)*2^n, H'> should be mu*B_blinding + <l, G> + <r, H'>,
+        // so P - mu*B_blinding + t_hat*Q is the statement of the inner-product proof
+        let h_prime = h_prime(h, &y);
+        let h_prime_scalars: Vec<Scalar> = (0..nm)
+            .map(|i| z * y_powers[i] + z_2_powers[i / n] * two_powers[i % n])
+            .collect();
+        let p = self.a + self.s * x - g.iter().sum::<ProjectivePoint>() * z
+            + multiscalar_mul(&h_prime_scalars, &h_prime)
+            - pc_gens.b_blinding * self.mu
+            + q * self.t_hat;
+
+        self.ipp.verify(&mut transcript, &q, g, &h_prime, &p)
+    }
+
+    /// A || S || T1 || T2 || tau_x || mu || t_hat || inner-product proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(4 * POINT_LEN + 3 * SCALAR_LEN + self.ipp.serialized_len());
+        for point in [&self.a, &self.s, &self.t1, &self.t2] {
+            bytes.extend_from_slice(&point.to_bytes());
+        }
+        for scalar in [&self.tau_x, &self.mu, &self.t_hat] {
+            bytes.extend_from_slice(&scalar.to_bytes());
+        }
+        bytes.extend(self.ipp.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RangeProofError> {
+        let header_len = 4 * POINT_LEN + 3 * SCALAR_LEN;
+        if bytes.len() < header_len {
+            return Err(RangeProofError::InvalidEncoding);
+        }
+        let (header, ipp_bytes) = bytes.split_at(header_len);
+        let point =
+            |i: usize| encoding::point_from_bytes(&header[i * POINT_LEN..(i + 1) * POINT_LEN]);
+        let scalar = |i: usize| {
+            let start = 4 * POINT_LEN + i * SCALAR_LEN;
+            encoding::scalar_from_bytes(&header[start..start + SCALAR_LEN])
+        };
+        (|| {
+            Some(Self {
+                a: point(0)?,
+                s: point(1)?,
+                t1: point(2)?,
+                t2: point(3)?,
+                tau_x: scalar(0)?,
+                mu: scalar(1)?,
+                t_hat: scalar(2)?,
+                ipp: InnerProductProof::from_bytes(ipp_bytes)?,
+            })
+        })()
+        .ok_or(RangeProofError::InvalidEncoding)
+    }
+}
+
+fn check_aggregation_size(m: usize, bp_gens: &BulletproofGens) -> Result<(), RangeProofError> {
+    if !m.is_power_of_two() {
+        return Err(RangeProofError::InvalidAggregation);
+    }
+    if m > bp_gens.party_capacity() {
+        return Err(RangeProofError::InvalidGeneratorsLength);
+    }
+    Ok(())
+}
+
+/// [1, x, x^2, ..., x^(n-1)]
+fn powers(x: &Scalar, n: usize) -> Vec<Scalar> {
+    core::iter::successors(Some(Scalar::ONE), |acc| Some(acc * x))
+        .take(n)
+        .collect()
+}
+
+/// H'_i = y^-i * H_i
+fn h_prime(h: &[ProjectivePoint], y: &Scalar) -> Vec<ProjectivePoint> {
+    let y_inv = y.invert().unwrap();
+    h.iter()
+        .zip(powers(&y_inv, h.len()))
+        .map(|(h_i, y_inv_i)| h_i * &y_inv_i)
+        .collect()
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum RangeProofError {
+    #[error("the number of aggregated values must be a non-zero power of 2")]
+    InvalidAggregation,
+    #[error("not enough generators for this many aggregated values")]
+    InvalidGeneratorsLength,
+    #[error("there must be exactly one blinding factor per value")]
+    WrongNumBlindings,
+    #[error("invalid encoding")]
+    InvalidEncoding,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    fn random_blindings(m: usize) -> Vec<Scalar> {
+        (0..m).map(|_| Scalar::random(&mut thread_rng())).collect()
+    }
+
+    #[test]
+    fn verify_single_range_proof_ok() {
+        let bp_gens = BulletproofGens::new(1);
+        let pc_gens = PedersenGens::default();
+        for value in [0, 1, 42, u64::MAX] {
+            let blinding = random_blindings(1)[0];
+            let (proof, commitment) =
+                RangeProof::prove_single("sid", 1, &bp_gens, &pc_gens, value, blinding).unwrap();
+
+            assert_eq!(commitment, pc_gens.commit(Scalar::from(value), blinding));
+            assert!(proof.verify("sid", 1, &bp_gens, &pc_gens, &[commitment]));
+            assert!(!proof.verify("sid", 2, &bp_gens, &pc_gens, &[commitment]));
+        }
+    }
+
+    #[test]
+    fn verify_aggregated_range_proof_ok() {
+        let bp_gens = BulletproofGens::new(4);
+        let pc_gens = PedersenGens::default();
+        let values = [7, u64::MAX, 0, 1 << 40];
+        let (proof, commitments) =
+            RangeProof::prove_multiple("sid", 1, &bp_gens, &pc_gens, &values, &random_blindings(4))
+                .unwrap();
+
+        assert!(proof.verify("sid", 1, &bp_gens, &pc_gens, &commitments));
+        // the commitments are bound to the proof, in order
+        let swapped = [
+            commitments[1],
+            commitments[0],
+            commitments[2],
+            commitments[3],
+        ];
+        assert!(!proof.verify("sid", 1, &bp_gens, &pc_gens, &swapped));
+        assert!(!proof.verify("sid", 1, &bp_gens, &pc_gens, &commitments[..2]));
+    }
+
+    #[test]
+    fn verify_out_of_range_commitment_false() {
+        let bp_gens = BulletproofGens::new(1);
+        let pc_gens = PedersenGens::default();
+        let blinding = random_blindings(1)[0];
+        let (proof, commitment) =
+            RangeProof::prove_single("sid", 1, &bp_gens, &pc_gens, u64::MAX, blinding).unwrap();
+
+        // a commitment to u64::MAX + 1 = 2^64, just outside the range
+        let out_of_range = commitment + pc_gens.b;
+        assert!(!proof.verify("sid", 1, &bp_gens, &pc_gens, &[out_of_range]));
+        // a commitment to -1
+        let negative = pc_gens.commit(-Scalar::ONE, blinding);
+        assert!(!proof.verify("sid", 1, &bp_gens, &pc_gens, &[negative]));
+    }
+
+    #[test]
+    fn invalid_aggregation_sizes_rejected() {
+        let bp_gens = BulletproofGens::new(2);
+        let pc_gens = PedersenGens::default();
+        assert_eq!(
+            RangeProof::prove_multiple(
+                "sid",
+                1,
+                &bp_gens,
+                &pc_gens,
+                &[1, 2, 3],
+                &random_blindings(3)
+            )
+            .unwrap_err(),
+            RangeProofError::InvalidAggregation
+        );
+        assert_eq!(
+            RangeProof::prove_multiple(
+                "sid",
+                1,
+                &bp_gens,
+                &pc_gens,
+                &[1, 2, 3, 4],
+                &random_blindings(4)
+            )
+            .unwrap_err(),
+            RangeProofError::InvalidGeneratorsLength
+        );
+        assert_eq!(
+            RangeProof::prove_multiple("sid", 1, &bp_gens, &pc_gens, &[1, 2], &random_blindings(1))
+                .unwrap_err(),
+            RangeProofError::WrongNumBlindings
+        );
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let bp_gens = BulletproofGens::new(2);
+        let pc_gens = PedersenGens::default();
+        let (proof, commitments) =
+            RangeProof::prove_multiple("sid", 1, &bp_gens, &pc_gens, &[3, 5], &random_blindings(2))
+                .unwrap();
+
+        let bytes = proof.to_bytes();
+        // 4 points, 3 scalars, then log2(128) = 7 rounds of (L, R) and the final (a, b)
+        assert_eq!(
+            bytes.len(),
+            4 * POINT_LEN + 3 * SCALAR_LEN + 14 * POINT_LEN + 2 * SCALAR_LEN
+        );
+        let decoded = RangeProof::from_bytes(&bytes).unwrap();
+        assert!(decoded.verify("sid", 1, &bp_gens, &pc_gens, &commitments));
+        assert_eq!(
+            RangeProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(RangeProofError::InvalidEncoding)
+        );
+
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<RangeProof>(&json).unwrap(), proof);
+    }
+}
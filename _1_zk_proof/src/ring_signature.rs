@@ -0,0 +1,508 @@
+use crate::encoding::{self, POINT_LEN, SCALAR_LEN};
+use crate::transcript;
+use alloc::vec;
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::{ProjectivePoint, Scalar};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Domain separation tag for hashing a public key to the base point of its key image
+const KEY_IMAGE_DST: &[u8] = b"_1_zk_proof-ring-signature-key-image-v1";
+
+/// Ring signatures (AOS, Abe-Ohkubo-Suzuki) built from the Schnorr commit-challenge-response steps of `DLogProof`
+///
+/// A ring signature proves that the signer knows the secret key x of ONE of the public keys P_i of the ring, without revealing which one.
+/// Protocol (signer at index π, P_π = x*G):
+/// 1. Commitment: the signer generates a random number a, computes L = aG, and derives the challenge of the next member c_(π+1) = H(ring, L, m)
+/// 2. For every other member i, the signer "simulates" a Schnorr proof: picks a random response s_i, computes L_i = s_i*G + c_i*P_i and c_(i+1) = H(ring, L_i, m)
+/// 3. Response: the ring of challenges comes back to the signer, who closes it with s_π = a - c_π * x (so that s_π*G + c_π*P_π == aG == L)
+/// 4. Verification: starting from c_0, the verifier recomputes every L_i and c_(i+1), and checks that the ring closes: c_n == c_0
+///
+/// Only the signer's member needs x to close the ring, and every s_i is uniformly random, so the signature doesn't reveal π
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RingKeys")]
+pub struct Ring {
+    #[serde(with = "encoding::serde_points")]
+    keys: Vec<ProjectivePoint>,
+}
+
+/// The serialized form of a [`Ring`], deserialized through [`Ring::new`] so that its checks can't be skipped
+#[derive(Deserialize)]
+struct RingKeys {
+    #[serde(with = "encoding::serde_points")]
+    keys: Vec<ProjectivePoint>,
+}
+impl TryFrom<RingKeys> for Ring {
+    type Error = RingSigError;
+
+    fn try_from(ring: RingKeys) -> Result<Self, Self::Error> {
+        Self::new(ring.keys)
+    }
+}
+
+impl Ring {
+    /// Build a ring from the public keys of its members. The ring can't be empty nor contain the identity point
+    pub fn new(keys: Vec<ProjectivePoint>) -> Result<Self, RingSigError> {
+        if keys.is_empty() {
+            return Err(RingSigError::EmptyRing);
+        }
+        if keys.contains(&ProjectivePoint::IDENTITY) {
+            return Err(RingSigError::InvalidKey);
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn keys(&self) -> &[ProjectivePoint] {
+        &self.keys
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Concatenation of the compressed SEC1 encodings of the keys
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.keys.iter().flat_map(|key| key.to_bytes()).collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RingSigError> {
+        let chunks = bytes.chunks_exact(POINT_LEN);
+        if !chunks.remainder().is_empty() {
+            return Err(RingSigError::InvalidEncoding);
+        }
+        let keys = chunks
+            .map(encoding::point_from_bytes)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(RingSigError::InvalidEncoding)?;
+        Self::new(keys)
+    }
+
+    /// Check that the signer's secret key x matches the key at `signer_index`
+    fn check_signer(&self, signer_index: usize, x: &Scalar) -> Result<(), RingSigError> {
+        let key = self
+            .keys
+            .get(signer_index)
+            .ok_or(RingSigError::SignerIndexOutOfRange)?;
+        if ProjectivePoint::mul_by_generator(x) != *key {
+            return Err(RingSigError::KeyNotInRing);
+        }
+        Ok(())
+    }
+
+    /// Whether a signature with `responses` responses can be checked against this ring.
+    /// An empty ring would accept any signature, so it is rejected even if built without [`Ring::new`]
+    fn can_verify(&self, responses: usize) -> bool {
+        !self.is_empty()
+            && responses == self.len()
+            && !self.keys.contains(&ProjectivePoint::IDENTITY)
+    }
+}
+
+/// Non-linkable ring signature: two signatures by the same member can't be told apart from signatures by two different members
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingSignature {
+    /// The challenge of the first ring member, the starting point of verification
+    c0: Scalar,
+    /// One response per ring member
+    s: Vec<Scalar>,
+}
+impl RingSignature {
+    /// Sign `msg` on behalf of the ring, with the secret key x of the member at `signer_index`
+    #[cfg(feature = "std")]
+    pub fn sign(
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        ring: &Ring,
+        signer_index: usize,
+        x: Scalar,
+    ) -> Result<Self, RingSigError> {
+        Self::sign_with_rng(
+            sid,
+            pid,
+            msg,
+            ring,
+            signer_index,
+            x,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    pub fn sign_with_rng(
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        ring: &Ring,
+        signer_index: usize,
+        x: Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, RingSigError> {
+        ring.check_signer(signer_index, &x)?;
+        let n = ring.len();
+        let mut c = vec![Scalar::ZERO; n];
+        let mut s = vec![Scalar::ZERO; n];
+
+        // a is a random Scalar, the nonce of the signer's (real) Schnorr proof
+        let a = Scalar::generate_vartime(rng);
+        let l = ProjectivePoint::mul_by_generator(&a);
+        c[(signer_index + 1) % n] = Self::calc_challenge(sid, pid, ring, l, msg);
+
+        // simulate the proofs of the other members, going around the ring
+        for offset in 1..n {
+            let i = (signer_index + offset) % n;
+            s[i] = Scalar::generate_vartime(rng);
+            let l_i = ProjectivePoint::mul_by_generator(&s[i]) + ring.keys[i] * c[i];
+            c[(i + 1) % n] = Self::calc_challenge(sid, pid, ring, l_i, msg);
+        }
+
+        // close the ring
+        s[signer_index] = a - c[signer_index] * x;
+
+        Ok(Self { c0: c[0], s })
+    }
+
+    /// Verify that `msg` was signed by a member of the ring, without learning which one
+    pub fn verify(&self, sid: &str, pid: u64, msg: &[u8], ring: &Ring) -> bool {
+        if !ring.can_verify(self.s.len()) {
+            return false;
+        }
+        let mut c = self.c0;
+        for (s_i, key) in self.s.iter().zip(ring.keys()) {
+            let l_i = ProjectivePoint::mul_by_generator(s_i) + key * &c;
+            c = Self::calc_challenge(sid, pid, ring, l_i, msg);
+        }
+        c == self.c0
+    }
+
+    /// c0 || s_0 || ... || s_(n-1), every scalar encoded on 32 big-endian bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        core::iter::once(&self.c0)
+            .chain(&self.s)
+            .flat_map(|scalar| scalar.to_bytes())
+            .collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RingSigError> {
+        let mut scalars = decode_scalars(bytes)?;
+        if scalars.len() < 2 {
+            return Err(RingSigError::InvalidEncoding);
+        }
+        let s = scalars.split_off(1);
+        Ok(Self { c0: scalars[0], s })
+    }
+
+    fn calc_challenge(sid: &str, pid: u64, ring: &Ring, l: ProjectivePoint, msg: &[u8]) -> Scalar {
+        let mut points = ring.keys.clone();
+        points.push(l);
+        transcript::challenge(sid.as_bytes(), pid, &points, msg)
+    }
+}
+
+/// Linkable ring signature (LSAG): same as [`RingSignature`], plus a key image I = x * Hp(P)
+///
+/// The key image only depends on the signer's key pair, so two signatures by the same member have the same key image (and can be linked),
+/// while still not revealing which member of the ring signed.
+/// Each member's Schnorr proof is extended to also prove that I has the same discrete log w.r.t. Hp(P) as P has w.r.t. G:
+/// L_i = s_i*G + c_i*P_i, R_i = s_i*Hp(P_i) + c_i*I, c_(i+1) = H(ring, I, L_i, R_i, m)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkableRingSignature {
+    /// I = x * Hp(P), where x is the signer's secret key and P = xG their public key
+    #[serde(with = "encoding::serde_point")]
+    key_image: ProjectivePoint,
+    c0: Scalar,
+    s: Vec<Scalar>,
+}
+impl LinkableRingSignature {
+    /// Sign `msg` on behalf of the ring, with the secret key x of the member at `signer_index`
+    #[cfg(feature = "std")]
+    pub fn sign(
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        ring: &Ring,
+        signer_index: usize,
+        x: Scalar,
+    ) -> Result<Self, RingSigError> {
+        Self::sign_with_rng(
+            sid,
+            pid,
+            msg,
+            ring,
+            signer_index,
+            x,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    pub fn sign_with_rng(
+        sid: &str,
+        pid: u64,
+        msg: &[u8],
+        ring: &Ring,
+        signer_index: usize,
+        x: Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, RingSigError> {
+        ring.check_signer(signer_index, &x)?;
+        let n = ring.len();
+        let mut c = vec![Scalar::ZERO; n];
+        let mut s = vec![Scalar::ZERO; n];
+
+        let key_image = key_image(&x);
+
+        let a = Scalar::generate_vartime(rng);
+        let l = ProjectivePoint::mul_by_generator(&a);
+        let r = hash_key_to_point(&ring.keys[signer_index]) * a;
+        c[(signer_index + 1) % n] = Self::calc_challenge(sid, pid, ring, key_image, l, r, msg);
+
+        for offset in 1..n {
+            let i = (signer_index + offset) % n;
+            s[i] = Scalar::generate_vartime(rng);
+            let l_i = ProjectivePoint::mul_by_generator(&s[i]) + ring.keys[i] * c[i];
+            let r_i = hash_key_to_point(&ring.keys[i]) * s[i] + key_image * c[i];
+            c[(i + 1) % n] = Self::calc_challenge(sid, pid, ring, key_image, l_i, r_i, msg);
+        }
+
+        s[signer_index] = a - c[signer_index] * x;
+
+        Ok(Self {
+            key_image,
+            c0: c[0],
+            s,
+        })
+    }
+
+    /// Verify that `msg` was signed by a member of the ring, without learning which one
+    pub fn verify(&self, sid: &str, pid: u64, msg: &[u8], ring: &Ring) -> bool {
+        if !ring.can_verify(self.s.len()) || self.key_image == ProjectivePoint::IDENTITY {
+            return false;
+        }
+        let mut c = self.c0;
+        for (s_i, key) in self.s.iter().zip(ring.keys()) {
+            let l_i = ProjectivePoint::mul_by_generator(s_i) + key * &c;
+            let r_i = hash_key_to_point(key) * s_i + self.key_image * c;
+            c = Self::calc_challenge(sid, pid, ring, self.key_image, l_i, r_i, msg);
+        }
+        c == self.c0
+    }
+
+    pub fn key_image(&self) -> &ProjectivePoint {
+        &self.key_image
+    }
+
+    /// Two valid signatures are linked if they were produced with the same secret key, whatever the ring or message
+    pub fn is_linked(&self, other: &Self) -> bool {
+        self.key_image == other.key_image
+    }
+
+    /// key_image (33 bytes) || c0 || s_0 || ... || s_(n-1)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.key_image.to_bytes().to_vec();
+        bytes.extend(
+            core::iter::once(&self.c0)
+                .chain(&self.s)
+                .flat_map(|scalar| scalar.to_bytes()),
+        );
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RingSigError> {
+        if bytes.len() < POINT_LEN {
+            return Err(RingSigError::InvalidEncoding);
+        }
+        let (key_image_bytes, scalar_bytes) = bytes.split_at(POINT_LEN);
+        let key_image =
+            encoding::point_from_bytes(key_image_bytes).ok_or(RingSigError::InvalidEncoding)?;
+        let RingSignature { c0, s } = RingSignature::from_bytes(scalar_bytes)?;
+        Ok(Self { key_image, c0, s })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn calc_challenge(
+        sid: &str,
+        pid: u64,
+        ring: &Ring,
+        key_image: ProjectivePoint,
+        l: ProjectivePoint,
+        r: ProjectivePoint,
+        msg: &[u8],
+    ) -> Scalar {
+        let mut points = ring.keys.clone();
+        points.extend([key_image, l, r]);
+        transcript::challenge(sid.as_bytes(), pid, &points, msg)
+    }
+}
+
+/// The key image of a secret key x: I = x * Hp(xG)
+pub fn key_image(x: &Scalar) -> ProjectivePoint {
+    hash_key_to_point(&ProjectivePoint::mul_by_generator(x)) * x
+}
+
+/// Hp: hash a public key to a curve point with unknown discrete log
+fn hash_key_to_point(key: &ProjectivePoint) -> ProjectivePoint {
+    transcript::hash_to_point(KEY_IMAGE_DST, &key.to_bytes())
+}
+
+fn decode_scalars(bytes: &[u8]) -> Result<Vec<Scalar>, RingSigError> {
+    let chunks = bytes.chunks_exact(SCALAR_LEN);
+    if !chunks.remainder().is_empty() {
+        return Err(RingSigError::InvalidEncoding);
+    }
+    chunks
+        .map(encoding::scalar_from_bytes)
+        .collect::<Option<Vec<_>>>()
+        .ok_or(RingSigError::InvalidEncoding)
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum RingSigError {
+    #[error("the ring must contain at least one public key")]
+    EmptyRing,
+    #[error("the ring can't contain the identity point")]
+    InvalidKey,
+    #[error("signer index is out of the ring's range")]
+    SignerIndexOutOfRange,
+    #[error("the secret key doesn't match the ring member at the signer index")]
+    KeyNotInRing,
+    #[error("invalid encoding")]
+    InvalidEncoding,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    fn random_ring(n: usize) -> (Vec<Scalar>, Ring) {
+        let secrets: Vec<Scalar> = (0..n)
+            .map(|_| Scalar::generate_vartime(&mut thread_rng()))
+            .collect();
+        let keys = secrets
+            .iter()
+            .map(ProjectivePoint::mul_by_generator)
+            .collect();
+        (secrets, Ring::new(keys).unwrap())
+    }
+
+    #[test]
+    fn ring_signature_valid_for_every_signer_index() {
+        let (secrets, ring) = random_ring(5);
+        for (i, x) in secrets.into_iter().enumerate() {
+            let sig = RingSignature::sign("sid", 1, b"msg", &ring, i, x).unwrap();
+            assert!(sig.verify("sid", 1, b"msg", &ring));
+        }
+    }
+
+    #[test]
+    fn ring_signature_invalid_for_other_message_sid_or_ring() {
+        let (secrets, ring) = random_ring(4);
+        let sig = RingSignature::sign("sid", 1, b"msg", &ring, 2, secrets[2]).unwrap();
+
+        assert!(!sig.verify("sid", 1, b"other msg", &ring));
+        assert!(!sig.verify("other sid", 1, b"msg", &ring));
+        assert!(!sig.verify("sid", 2, b"msg", &ring));
+        let (_, other_ring) = random_ring(4);
+        assert!(!sig.verify("sid", 1, b"msg", &other_ring));
+    }
+
+    #[test]
+    fn sign_with_key_outside_ring_fails() {
+        let (secrets, ring) = random_ring(3);
+        let outsider = Scalar::generate_vartime(&mut thread_rng());
+        assert_eq!(
+            RingSignature::sign("sid", 1, b"msg", &ring, 0, outsider),
+            Err(RingSigError::KeyNotInRing)
+        );
+        assert_eq!(
+            LinkableRingSignature::sign("sid", 1, b"msg", &ring, 3, secrets[0]),
+            Err(RingSigError::SignerIndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn linkable_signatures_by_same_key_are_linked() {
+        let (secrets, ring) = random_ring(4);
+        let (_, other_ring) = random_ring(2);
+        let other_ring = Ring::new([other_ring.keys(), &ring.keys()[1..2]].concat()).unwrap();
+
+        let sig1 = LinkableRingSignature::sign("sid", 1, b"vote 1", &ring, 1, secrets[1]).unwrap();
+        let sig2 =
+            LinkableRingSignature::sign("sid2", 2, b"vote 2", &other_ring, 2, secrets[1]).unwrap();
+        let sig3 = LinkableRingSignature::sign("sid", 1, b"vote 1", &ring, 3, secrets[3]).unwrap();
+
+        assert!(sig1.verify("sid", 1, b"vote 1", &ring));
+        assert!(sig2.verify("sid2", 2, b"vote 2", &other_ring));
+        assert!(sig3.verify("sid", 1, b"vote 1", &ring));
+        assert!(sig1.is_linked(&sig2));
+        assert!(!sig1.is_linked(&sig3));
+    }
+
+    #[test]
+    fn linkable_signature_with_forged_key_image_invalid() {
+        let (secrets, ring) = random_ring(3);
+        let mut sig = LinkableRingSignature::sign("sid", 1, b"msg", &ring, 0, secrets[0]).unwrap();
+        sig.key_image = key_image(&secrets[1]);
+        assert!(!sig.verify("sid", 1, b"msg", &ring));
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let (secrets, ring) = random_ring(3);
+        let sig = RingSignature::sign("sid", 1, b"msg", &ring, 1, secrets[1]).unwrap();
+        let linkable = LinkableRingSignature::sign("sid", 1, b"msg", &ring, 1, secrets[1]).unwrap();
+
+        assert_eq!(Ring::from_bytes(&ring.to_bytes()).unwrap(), ring);
+        assert_eq!(RingSignature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+        assert_eq!(
+            LinkableRingSignature::from_bytes(&linkable.to_bytes()).unwrap(),
+            linkable
+        );
+
+        let json = serde_json::to_string(&(&ring, &linkable)).unwrap();
+        let (ring2, linkable2): (Ring, LinkableRingSignature) =
+            serde_json::from_str(&json).unwrap();
+        assert!(linkable2.verify("sid", 1, b"msg", &ring2));
+    }
+
+    #[test]
+    fn empty_ring_cant_be_deserialized_nor_forged_against() {
+        let json = r#"{"keys":[]}"#;
+        assert!(serde_json::from_str::<Ring>(json).is_err());
+
+        // without any member, the ring would "close" for any c0
+        let empty = Ring { keys: vec![] };
+        let c0 = Scalar::generate_vartime(&mut thread_rng());
+        let forged = RingSignature { c0, s: vec![] };
+        assert!(!forged.verify("sid", 1, b"msg", &empty));
+        let forged = LinkableRingSignature {
+            key_image: key_image(&c0),
+            c0,
+            s: vec![],
+        };
+        assert!(!forged.verify("sid", 1, b"msg", &empty));
+
+        let identity = Ring {
+            keys: vec![ProjectivePoint::IDENTITY],
+        };
+        let forged = RingSignature { c0, s: vec![c0] };
+        assert!(!forged.verify("sid", 1, b"msg", &identity));
+    }
+
+    #[test]
+    fn malformed_encodings_rejected() {
+        let (secrets, ring) = random_ring(2);
+        let sig = RingSignature::sign("sid", 1, b"msg", &ring, 0, secrets[0]).unwrap();
+
+        let bytes = sig.to_bytes();
+        assert!(RingSignature::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(RingSignature::from_bytes(&[0xff; 64]).is_err());
+        assert!(Ring::from_bytes(&[]).is_err());
+        assert!(Ring::from_bytes(&[0u8; POINT_LEN]).is_err());
+    }
+}
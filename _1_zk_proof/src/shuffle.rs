@@ -0,0 +1,548 @@
+use crate::elgamal::Ciphertext;
+use crate::encoding::{self, POINT_LEN, SCALAR_LEN};
+use crate::inner_product_proof::{inner_product, multiscalar_mul};
+use crate::transcript::{self, Transcript};
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::elliptic_curve::Field;
+use k256::{ProjectivePoint, Scalar};
+use rand::seq::SliceRandom;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+const TRANSCRIPT_LABEL: &[u8] = b"_1_zk_proof-shuffle-v1";
+/// Domain separation of the u_i challenges, derived from one transcript challenge
+const CHALLENGE_VECTOR_LABEL: &[u8] = b"_1_zk_proof-shuffle-u-v1";
+
+/// The generators h, h_1..h_N of the permutation commitments, hashed to the curve so that nobody knows their discrete logs
+///
+/// Computing them is expensive: create them once (with the size of the biggest batch to shuffle) and reuse them
+#[derive(Debug, Clone)]
+pub struct ShuffleGens {
+    h: ProjectivePoint,
+    h_vec: Vec<ProjectivePoint>,
+}
+impl ShuffleGens {
+    /// Generators for shuffles of up to `capacity` ciphertexts
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            h: transcript::hash_to_point(b"_1_zk_proof-shuffle-h-v1", b""),
+            h_vec: (0..capacity as u64)
+                .map(|i| transcript::hash_to_point(b"_1_zk_proof-shuffle-H-v1", &i.to_le_bytes()))
+                .collect(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.h_vec.len()
+    }
+}
+
+/// Proof that a list of ElGamal ciphertexts is a shuffle of another: a permutation of re-encryptions of the input ciphertexts,
+/// without revealing the permutation (so nobody can link an output ciphertext to an input one, e.g. a decrypted vote to its voter)
+///
+/// Terelius-Wikström proof of shuffle (commitment-consistent, like Neff's and Bayer-Groth's), as specified in
+/// "Pseudo-Code Algorithms for Verifiable Re-Encryption Mix-Nets" (Haenni et al. 2017), written additively.
+/// For N inputs e_j and outputs e'_i = ReEnc(e_ψ(i), r'_ψ(i)):
+/// 1. Permutation commitment: c_ψ(i) = r_ψ(i) * G + h_i, a Pedersen commitment to the permutation matrix
+/// 2. Challenges u_j = H(transcript, j), permuted as u'_i = u_ψ(i).
+///    Commitment chain: ĉ_0 = h, ĉ_i = r̂_i * G + u'_i * ĉ_{i-1}, so that ĉ_N = r̂ * G + (Π u_j) * h
+/// 3. A sigma protocol proving knowledge of the openings, with the challenge c = H(transcript, commitments):
+///    - Σ c_j - Σ h_i = r̄ * G: c commits to a matrix whose rows sum to 1
+///    - ĉ_N - (Π u_j) * h = r̂ * G: the committed u'_i have the same product as the u_j
+///    - Σ u_j * c_j = r * G + Σ u'_i * h_i: the committed matrix maps u to u' (with the previous checks: it is a permutation matrix)
+///    - Σ u_j * e_j = Σ u'_i * e'_i - r'' * (PK, G): the outputs are re-encryptions of the inputs, permuted by that matrix
+///
+/// Proof size: 3N points and 2N scalars. Proving and verifying take about 9N scalar multiplications each
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShuffleProof {
+    /// c_j, commitment to the permutation
+    #[serde(with = "encoding::serde_points")]
+    permutation_commitments: Vec<ProjectivePoint>,
+    /// ĉ_i, commitment chain of the permuted challenges
+    #[serde(with = "encoding::serde_points")]
+    chain_commitments: Vec<ProjectivePoint>,
+    #[serde(with = "encoding::serde_points")]
+    t: Vec<ProjectivePoint>,
+    #[serde(with = "encoding::serde_points")]
+    t_hat: Vec<ProjectivePoint>,
+    s: Vec<Scalar>,
+    s_hat: Vec<Scalar>,
+    s_prime: Vec<Scalar>,
+}
+
+/// Number of t commitments (t1, t2, t3, t4 = (t4_a, t4_b)) and s responses (s1, s2, s3, s4)
+const NUM_T: usize = 5;
+const NUM_S: usize = 4;
+
+/// The Fiat-Shamir challenges of a shuffle proof, hashing the public key, the ciphertexts and the commitments of the proof
+struct Challenges {
+    /// u_j, indexed by input
+    u: Vec<Scalar>,
+    c: Scalar,
+}
+impl Challenges {
+    fn new(
+        sid: &str,
+        pid: u64,
+        public_key: &ProjectivePoint,
+        input: &[Ciphertext],
+        output: &[Ciphertext],
+        proof: &ShuffleProof,
+    ) -> Self {
+        let mut transcript = Transcript::new(sid.as_bytes(), pid, TRANSCRIPT_LABEL);
+        transcript.append_point(public_key);
+        for ciphertext in input.iter().chain(output) {
+            transcript.append_point(&ciphertext.a);
+            transcript.append_point(&ciphertext.b);
+        }
+        for c_j in &proof.permutation_commitments {
+            transcript.append_point(c_j);
+        }
+        // one challenge hashing everything so far, expanded into u_1..u_N (instead of N challenges each hashing the whole transcript)
+        let seed = transcript.challenge().to_bytes();
+        let u = (0..input.len() as u64)
+            .map(|j| {
+                let msg = [CHALLENGE_VECTOR_LABEL, &seed, &j.to_le_bytes()].concat();
+                transcript::challenge(sid.as_bytes(), pid, &[], &msg)
+            })
+            .collect();
+
+        for point in proof
+            .chain_commitments
+            .iter()
+            .chain(&proof.t)
+            .chain(&proof.t_hat)
+        {
+            transcript.append_point(point);
+        }
+        Self {
+            u,
+            c: transcript.challenge(),
+        }
+    }
+}
+
+impl ShuffleProof {
+    /// Shuffle and re-encrypt `input` under `public_key` with a random permutation, and prove it
+    #[cfg(feature = "std")]
+    pub fn shuffle(
+        sid: &str,
+        pid: u64,
+        gens: &ShuffleGens,
+        public_key: &ProjectivePoint,
+        input: &[Ciphertext],
+    ) -> Result<(Vec<Ciphertext>, Self), ShuffleError> {
+        Self::shuffle_with_rng(sid, pid, gens, public_key, input, &mut rand::thread_rng())
+    }
+
+    pub fn shuffle_with_rng(
+        sid: &str,
+        pid: u64,
+        gens: &ShuffleGens,
+        public_key: &ProjectivePoint,
+        input: &[Ciphertext],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Vec<Ciphertext>, Self), ShuffleError> {
+        let n = input.len();
+        check_size(gens, n)?;
+        // output i is input ψ(i), re-encrypted with randomness r'_ψ(i)
+        let mut permutation: Vec<usize> = (0..n).collect();
+        permutation.shuffle(rng);
+        let reencryption_randomness = random_scalars(n, rng);
+        let output: Vec<Ciphertext> = permutation
+            .iter()
+            .map(|&j| input[j].reencrypt(public_key, &reencryption_randomness[j]))
+            .collect();
+
+        // 1. permutation commitment: c_ψ(i) = r_ψ(i) * G + h_i
+        let r = random_scalars(n, rng);
+        let mut permutation_commitments = alloc::vec![ProjectivePoint::IDENTITY; n];
+        for (i, &j) in permutation.iter().enumerate() {
+            permutation_commitments[j] = ProjectivePoint::mul_by_generator(&r[j]) + gens.h_vec[i];
+        }
+
+        let mut proof = Self {
+            permutation_commitments,
+            chain_commitments: Vec::new(),
+            t: Vec::new(),
+            t_hat: Vec::new(),
+            s: Vec::new(),
+            s_hat: Vec::new(),
+            s_prime: Vec::new(),
+        };
+        let u = Challenges::new(sid, pid, public_key, input, &output, &proof).u;
+        let u_permuted: Vec<Scalar> = permutation.iter().map(|&j| u[j]).collect();
+
+        // 2. commitment chain: ĉ_i = r̂_i * G + u'_i * ĉ_{i-1}
+        let r_hat = random_scalars(n, rng);
+        let mut previous = gens.h;
+        for (r_hat_i, u_i) in r_hat.iter().zip(&u_permuted) {
+            previous = ProjectivePoint::mul_by_generator(r_hat_i) + previous * u_i;
+            proof.chain_commitments.push(previous);
+        }
+        let previous_chain = chain_predecessors(gens, &proof.chain_commitments);
+
+        // 3. sigma protocol commitments
+        let omega = random_scalars(NUM_S, rng);
+        let omega_hat = random_scalars(n, rng);
+        let omega_prime = random_scalars(n, rng);
+        let (output_a, output_b): (Vec<_>, Vec<_>) = output.iter().map(|e| (e.a, e.b)).unzip();
+        proof.t = alloc::vec![
+            ProjectivePoint::mul_by_generator(&omega[0]),
+            ProjectivePoint::mul_by_generator(&omega[1]),
+            ProjectivePoint::mul_by_generator(&omega[2])
+                + multiscalar_mul(&omega_prime, &gens.h_vec[..n]),
+            multiscalar_mul(&omega_prime, &output_a) - public_key * &omega[3],
+            multiscalar_mul(&omega_prime, &output_b) - ProjectivePoint::mul_by_generator(&omega[3]),
+        ];
+        proof.t_hat = omega_hat
+            .iter()
+            .zip(&omega_prime)
+            .zip(&previous_chain)
+            .map(|((omega_hat_i, omega_prime_i), previous)| {
+                ProjectivePoint::mul_by_generator(omega_hat_i) + previous * omega_prime_i
+            })
+            .collect();
+
+        // responses: s = ω - c * secret
+        let c = Challenges::new(sid, pid, public_key, input, &output, &proof).c;
+        // v_i = Π_{k > i} u'_k, so that r̂ = Σ r̂_i * v_i is the randomness of ĉ_N
+        let mut v = alloc::vec![Scalar::ONE; n];
+        for i in (1..n).rev() {
+            v[i - 1] = u_permuted[i] * v[i];
+        }
+        let secrets = [
+            r.iter().sum(),
+            inner_product(&r_hat, &v),
+            inner_product(&r, &u),
+            inner_product(&reencryption_randomness, &u),
+        ];
+        proof.s = omega
+            .iter()
+            .zip(&secrets)
+            .map(|(omega, secret)| omega - &(c * secret))
+            .collect();
+        proof.s_hat = omega_hat
+            .iter()
+            .zip(&r_hat)
+            .map(|(omega, r_hat)| omega - &(c * r_hat))
+            .collect();
+        proof.s_prime = omega_prime
+            .iter()
+            .zip(&u_permuted)
+            .map(|(omega, u)| omega - &(c * u))
+            .collect();
+
+        Ok((output, proof))
+    }
+
+    /// Check that `output` is a shuffle of re-encryptions of `input` under `public_key`
+    pub fn verify(
+        &self,
+        sid: &str,
+        pid: u64,
+        gens: &ShuffleGens,
+        public_key: &ProjectivePoint,
+        input: &[Ciphertext],
+        output: &[Ciphertext],
+    ) -> bool {
+        let n = input.len();
+        if check_size(gens, n).is_err()
+            || output.len() != n
+            || self.permutation_commitments.len() != n
+            || self.chain_commitments.len() != n
+            || self.t.len() != NUM_T
+            || self.t_hat.len() != n
+            || self.s.len() != NUM_S
+            || self.s_hat.len() != n
+            || self.s_prime.len() != n
+        {
+            return false;
+        }
+        let Challenges { u, c } = Challenges::new(sid, pid, public_key, input, output, self);
+        let h_vec = &gens.h_vec[..n];
+        let (s, t) = (&self.s, &self.t);
+        let g_mul = ProjectivePoint::mul_by_generator;
+
+        // Σ c_j - Σ h_i
+        let c_bar: ProjectivePoint = self.permutation_commitments.iter().sum::<ProjectivePoint>()
+            - h_vec.iter().sum::<ProjectivePoint>();
+        // ĉ_N - (Π u_j) * h
+        let u_product: Scalar = u.iter().product();
+        let c_hat = self.chain_commitments[n - 1] - gens.h * u_product;
+        // Σ u_j * c_j
+        let c_tilde = multiscalar_mul(&u, &self.permutation_commitments);
+        // Σ u_j * e_j
+        let (input_a, input_b): (Vec<_>, Vec<_>) = input.iter().map(|e| (e.a, e.b)).unzip();
+        let (output_a, output_b): (Vec<_>, Vec<_>) = output.iter().map(|e| (e.a, e.b)).unzip();
+        let a_tilde = multiscalar_mul(&u, &input_a);
+        let b_tilde = multiscalar_mul(&u, &input_b);
+
+        let expected_t = [
+            c_bar * c + g_mul(&s[0]),
+            c_hat * c + g_mul(&s[1]),
+            c_tilde * c + g_mul(&s[2]) + multiscalar_mul(&self.s_prime, h_vec),
+            a_tilde * c - public_key * &s[3] + multiscalar_mul(&self.s_prime, &output_a),
+            b_tilde * c - g_mul(&s[3]) + multiscalar_mul(&self.s_prime, &output_b),
+        ];
+        if expected_t[..] != t[..] {
+            return false;
+        }
+
+        let previous_chain = chain_predecessors(gens, &self.chain_commitments);
+        (0..n).all(|i| {
+            self.chain_commitments[i] * c
+                + g_mul(&self.s_hat[i])
+                + previous_chain[i] * self.s_prime[i]
+                == self.t_hat[i]
+        })
+    }
+
+    /// c_j || ĉ_i || t || t̂_i || s || ŝ_i || s'_i: 5 points and 4 scalars, plus 3 points and 2 scalars per ciphertext
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let points = self
+            .permutation_commitments
+            .iter()
+            .chain(&self.chain_commitments)
+            .chain(&self.t)
+            .chain(&self.t_hat)
+            .flat_map(|point| point.to_bytes());
+        let scalars = self
+            .s
+            .iter()
+            .chain(&self.s_hat)
+            .chain(&self.s_prime)
+            .flat_map(|scalar| scalar.to_bytes());
+        points.chain(scalars).collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShuffleError> {
+        let fixed_len = NUM_T * POINT_LEN + NUM_S * SCALAR_LEN;
+        let per_ciphertext_len = 3 * POINT_LEN + 2 * SCALAR_LEN;
+        let variable_len = bytes
+            .len()
+            .checked_sub(fixed_len)
+            .filter(|len| len % per_ciphertext_len == 0)
+            .ok_or(ShuffleError::InvalidEncoding)?;
+        let n = variable_len / per_ciphertext_len;
+
+        let (points, scalars) = bytes.split_at((3 * n + NUM_T) * POINT_LEN);
+        let mut points = points
+            .chunks_exact(POINT_LEN)
+            .map(encoding::point_from_bytes)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ShuffleError::InvalidEncoding)?;
+        let mut scalars = scalars
+            .chunks_exact(SCALAR_LEN)
+            .map(encoding::scalar_from_bytes)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ShuffleError::InvalidEncoding)?;
+
+        let t_hat = points.split_off(2 * n + NUM_T);
+        let t = points.split_off(2 * n);
+        let chain_commitments = points.split_off(n);
+        let s_prime = scalars.split_off(NUM_S + n);
+        let s_hat = scalars.split_off(NUM_S);
+        Ok(Self {
+            permutation_commitments: points,
+            chain_commitments,
+            t,
+            t_hat,
+            s: scalars,
+            s_hat,
+            s_prime,
+        })
+    }
+}
+
+fn random_scalars(n: usize, rng: &mut (impl RngCore + CryptoRng)) -> Vec<Scalar> {
+    (0..n).map(|_| Scalar::random(&mut *rng)).collect()
+}
+
+/// ĉ_{i-1} for every i: h, ĉ_1, ..., ĉ_{N-1}
+fn chain_predecessors(
+    gens: &ShuffleGens,
+    chain_commitments: &[ProjectivePoint],
+) -> Vec<ProjectivePoint> {
+    let n = chain_commitments.len();
+    core::iter::once(gens.h)
+        .chain(chain_commitments[..n.saturating_sub(1)].iter().copied())
+        .collect()
+}
+
+fn check_size(gens: &ShuffleGens, n: usize) -> Result<(), ShuffleError> {
+    if n == 0 {
+        return Err(ShuffleError::Empty);
+    }
+    if n > gens.capacity() {
+        return Err(ShuffleError::NotEnoughGenerators);
+    }
+    Ok(())
+}
+
+/// The shuffle of one mix server of a cascade, who re-encrypts and shuffles the output of the previous one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mix {
+    pub pid: u64,
+    pub output: Vec<Ciphertext>,
+    pub proof: ShuffleProof,
+}
+
+/// Verify a whole mix-net: every mix of the cascade shuffled the output of the previous one, the first one the `input` ciphertexts (e.g. the ballots of an election)
+///
+/// Returns the output of the last mix, unlinkable to the input as long as one of the mix servers kept its permutation secret,
+/// or the index of the first mix whose shuffle is invalid
+pub fn verify_cascade<'a>(
+    sid: &str,
+    gens: &ShuffleGens,
+    public_key: &ProjectivePoint,
+    input: &'a [Ciphertext],
+    mixes: &'a [Mix],
+) -> Result<&'a [Ciphertext], ShuffleError> {
+    let mut previous = input;
+    for (index, mix) in mixes.iter().enumerate() {
+        if !mix
+            .proof
+            .verify(sid, mix.pid, gens, public_key, previous, &mix.output)
+        {
+            return Err(ShuffleError::InvalidShuffle { mix: index });
+        }
+        previous = &mix.output;
+    }
+    Ok(previous)
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ShuffleError {
+    #[error("there must be at least one ciphertext to shuffle")]
+    Empty,
+    #[error("not enough generators for this many ciphertexts")]
+    NotEnoughGenerators,
+    #[error("the shuffle of mix {mix} is invalid")]
+    InvalidShuffle { mix: usize },
+    #[error("invalid encoding")]
+    InvalidEncoding,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    /// Encrypted votes v * G for v in 0..n
+    fn ballots(n: u64) -> (Scalar, ProjectivePoint, Vec<Ciphertext>) {
+        let sk = Scalar::random(&mut thread_rng());
+        let pk = ProjectivePoint::mul_by_generator(&sk);
+        let ballots = (0..n)
+            .map(|v| {
+                let vote = ProjectivePoint::mul_by_generator(&Scalar::from(v));
+                Ciphertext::encrypt_with_rng(&pk, &vote, &mut thread_rng())
+            })
+            .collect();
+        (sk, pk, ballots)
+    }
+
+    #[test]
+    fn shuffle_verify_ok_and_decrypts_to_a_permutation() {
+        let gens = ShuffleGens::new(8);
+        let (sk, pk, input) = ballots(8);
+        let (output, proof) = ShuffleProof::shuffle("sid", 1, &gens, &pk, &input).unwrap();
+        assert!(proof.verify("sid", 1, &gens, &pk, &input, &output));
+        assert!(!proof.verify("other sid", 1, &gens, &pk, &input, &output));
+        assert!(!proof.verify("sid", 2, &gens, &pk, &input, &output));
+
+        let decrypt = |ciphertexts: &[Ciphertext]| {
+            let mut votes: Vec<_> = ciphertexts
+                .iter()
+                .map(|e| e.decrypt(&sk).to_bytes())
+                .collect();
+            votes.sort();
+            votes
+        };
+        assert_eq!(decrypt(&output), decrypt(&input));
+        // re-encrypted: no output ciphertext appears in the input
+        assert!(output.iter().all(|e| !input.contains(e)));
+
+        let single = &input[..1];
+        let (output, proof) = ShuffleProof::shuffle("sid", 1, &gens, &pk, single).unwrap();
+        assert!(proof.verify("sid", 1, &gens, &pk, single, &output));
+    }
+
+    #[test]
+    fn tampered_shuffle_is_rejected() {
+        let gens = ShuffleGens::new(4);
+        let (_, pk, input) = ballots(4);
+        let (output, proof) = ShuffleProof::shuffle("sid", 1, &gens, &pk, &input).unwrap();
+
+        // a ballot replaced by another vote
+        let mut replaced = output.clone();
+        replaced[0] =
+            Ciphertext::encrypt_with_rng(&pk, &ProjectivePoint::GENERATOR, &mut thread_rng());
+        assert!(!proof.verify("sid", 1, &gens, &pk, &input, &replaced));
+
+        // outputs reordered after proving, or proven against other inputs
+        let mut swapped = output.clone();
+        swapped.swap(0, 1);
+        assert!(!proof.verify("sid", 1, &gens, &pk, &input, &swapped));
+        let (_, _, other_input) = ballots(4);
+        assert!(!proof.verify("sid", 1, &gens, &pk, &other_input, &output));
+        assert!(!proof.verify("sid", 1, &gens, &pk, &input[..3], &output[..3]));
+    }
+
+    #[test]
+    fn cascade_verification_identifies_the_cheating_mix() {
+        let gens = ShuffleGens::new(5);
+        let (_, pk, input) = ballots(5);
+        let mut mixes = Vec::new();
+        let mut previous = input.clone();
+        for pid in 1..=3 {
+            let (output, proof) =
+                ShuffleProof::shuffle("election", pid, &gens, &pk, &previous).unwrap();
+            previous = output.clone();
+            mixes.push(Mix { pid, output, proof });
+        }
+        assert_eq!(
+            verify_cascade("election", &gens, &pk, &input, &mixes),
+            Ok(&previous[..])
+        );
+
+        mixes[1].output[2] = mixes[1].output[2].reencrypt(&pk, &Scalar::ONE);
+        assert_eq!(
+            verify_cascade("election", &gens, &pk, &input, &mixes),
+            Err(ShuffleError::InvalidShuffle { mix: 1 })
+        );
+    }
+
+    #[test]
+    fn encoding_roundtrip_and_sizes() {
+        let gens = ShuffleGens::new(3);
+        let (_, pk, input) = ballots(3);
+        let (output, proof) = ShuffleProof::shuffle("sid", 1, &gens, &pk, &input).unwrap();
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            5 * POINT_LEN + 4 * SCALAR_LEN + 3 * (3 * POINT_LEN + 2 * SCALAR_LEN)
+        );
+        assert_eq!(ShuffleProof::from_bytes(&bytes), Ok(proof.clone()));
+        assert_eq!(
+            ShuffleProof::from_bytes(&bytes[1..]),
+            Err(ShuffleError::InvalidEncoding)
+        );
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<ShuffleProof>(&json).unwrap(), proof);
+        assert!(proof.verify("sid", 1, &gens, &pk, &input, &output));
+
+        assert_eq!(
+            ShuffleProof::shuffle("sid", 1, &gens, &pk, &[]).unwrap_err(),
+            ShuffleError::Empty
+        );
+        let (_, pk, too_many) = ballots(4);
+        assert_eq!(
+            ShuffleProof::shuffle("sid", 1, &gens, &pk, &too_many).unwrap_err(),
+            ShuffleError::NotEnoughGenerators
+        );
+    }
+}
@@ -0,0 +1,625 @@
+//! Support code for [`define_proof!`](crate::define_proof), re-exported so that the generated code only depends on this crate
+use crate::encoding;
+use crate::transcript;
+use core::cell::Cell;
+use k256::elliptic_curve::ops::LinearCombinationExt;
+use k256::elliptic_curve::Field;
+
+pub use crate::encoding::{POINT_LEN, SCALAR_LEN};
+pub use crate::prepared_base::BasePoint;
+pub use alloc::vec;
+pub use alloc::vec::Vec;
+pub use k256::elliptic_curve::group::GroupEncoding;
+pub use k256::{ProjectivePoint, Scalar};
+#[cfg(feature = "std")]
+pub use rand::thread_rng;
+pub use rand::{CryptoRng, RngCore};
+
+/// Define a non-interactive zero-knowledge proof of knowledge of secret scalars satisfying linear discrete-log relations,
+/// i.e. a Schnorr proof generalized to several secrets and several statements (a sigma protocol), with the Fiat-Shamir transformation.
+///
+/// Syntax:
+/// ```ignore
+/// define_proof! {
+///     pedersen_opening,              // name of the generated module
+///     "pedersen-opening",            // label, hashed into the challenge
+///     (x, r),                        // secret scalars
+///     (X, Y),                        // instance points, specific to each proof
+///     (G, H, B)                      // common points, shared by all the proofs of a batch
+///     :
+///     X = (x * G), Y = (x * H + r * B)
+/// }
+/// ```
+///
+/// Protocol, for secrets x_j and statements L_i = Σ_j x_j * P_ij:
+/// 1. Commitment: the prover generates random nonces r_j, computes T_i = Σ_j r_j * P_ij for every statement
+/// 2. Challenge: c = H(sid, pid, [common points, instance points, T_i], label), the same conventions as `DLogProof`
+/// 3. Response: s_j = r_j + c * x_j
+/// 4. Verification: Σ_j s_j * P_ij == T_i + c * L_i for every statement
+///
+/// Common points are assigned as any [`BasePoint`]: a `ProjectivePoint`, or a [`PreparedBase`](crate::prepared_base::PreparedBase)
+/// to speed up the multiplications by a point other than G that is reused across proofs.
+///
+/// The generated module contains `ProveAssignments`, `VerifyAssignments`, `BatchVerifyAssignments`, `Responses`, `Proof`,
+/// and the functions `prove`, `verify` and `batch_verify`. Proofs are (de)serializable with serde or `to_bytes`/`from_bytes`.
+///
+/// `DLogProof` is the simplest instance: `(x), (Y), (B) : Y = (x * B)` with an empty label hashes exactly the same points, see [`dlog`]
+#[macro_export]
+macro_rules! define_proof {
+    (
+        $(#[$meta:meta])*
+        $proof_module_name:ident,
+        $proof_label:expr,
+        ( $($secret_var:ident),+ $(,)? ),
+        ( $($instance_var:ident),* $(,)? ),
+        ( $($common_var:ident),* $(,)? )
+        :
+        $($lhs:ident = ( $first_scalar:ident * $first_point:ident $(+ $scalar:ident * $point:ident)* )),+ $(,)?
+    ) => {
+        $(#[$meta])*
+        #[allow(non_snake_case, unused_variables, dead_code)]
+        pub mod $proof_module_name {
+            use $crate::sigma_proof::{self, vec, BasePoint, GroupEncoding, ProjectivePoint, Scalar, Vec, POINT_LEN, SCALAR_LEN};
+            use $crate::__serde;
+
+            pub const LABEL: &str = $proof_label;
+            const NUM_STATEMENTS: usize = [$(stringify!($lhs)),+].len();
+            const NUM_SECRETS: usize = [$(stringify!($secret_var)),+].len();
+
+            /// Secret and public variables, for the prover
+            #[derive(Clone, Copy)]
+            pub struct ProveAssignments<'a> {
+                $(pub $secret_var: &'a Scalar,)+
+                $(pub $instance_var: &'a ProjectivePoint,)*
+                $(pub $common_var: &'a dyn BasePoint,)*
+            }
+
+            /// Public variables, for the verifier
+            #[derive(Clone, Copy)]
+            pub struct VerifyAssignments<'a> {
+                $(pub $instance_var: &'a ProjectivePoint,)*
+                $(pub $common_var: &'a dyn BasePoint,)*
+            }
+
+            /// Public variables of a batch of proofs: one instance point per proof, common points shared by all
+            #[derive(Clone)]
+            pub struct BatchVerifyAssignments<'a> {
+                $(pub $instance_var: Vec<ProjectivePoint>,)*
+                $(pub $common_var: &'a dyn BasePoint,)*
+            }
+
+            /// One response per secret: s = r + c * secret
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, __serde::Serialize, __serde::Deserialize)]
+            #[serde(crate = "__serde")]
+            pub struct Responses {
+                $(pub $secret_var: Scalar,)+
+            }
+
+            /// One commitment per statement, in the order of the statements, and one response per secret
+            #[derive(Debug, Clone, PartialEq, Eq, __serde::Serialize, __serde::Deserialize)]
+            #[serde(crate = "__serde", try_from = "ProofParts")]
+            pub struct Proof {
+                #[serde(with = "sigma_proof::serde_points")]
+                commitments: Vec<ProjectivePoint>,
+                responses: Responses,
+            }
+
+            /// The serialized form of a [`Proof`], deserialized through [`Proof::from_parts`] so that its number of commitments is checked
+            #[derive(__serde::Deserialize)]
+            #[serde(crate = "__serde")]
+            struct ProofParts {
+                #[serde(with = "sigma_proof::serde_points")]
+                commitments: Vec<ProjectivePoint>,
+                responses: Responses,
+            }
+            impl TryFrom<ProofParts> for Proof {
+                type Error = sigma_proof::InvalidProofLength;
+
+                fn try_from(parts: ProofParts) -> Result<Self, Self::Error> {
+                    Self::from_parts(parts.commitments, parts.responses).ok_or(sigma_proof::InvalidProofLength)
+                }
+            }
+
+            impl Proof {
+                pub fn from_parts(commitments: Vec<ProjectivePoint>, responses: Responses) -> Option<Self> {
+                    (commitments.len() == NUM_STATEMENTS).then_some(Self { commitments, responses })
+                }
+
+                pub fn commitments(&self) -> &[ProjectivePoint] {
+                    &self.commitments
+                }
+
+                pub fn responses(&self) -> &Responses {
+                    &self.responses
+                }
+
+                pub const SERIALIZED_LEN: usize = NUM_STATEMENTS * POINT_LEN + NUM_SECRETS * SCALAR_LEN;
+
+                /// commitments (compressed SEC1), then responses (big-endian), in declaration order
+                pub fn to_bytes(&self) -> Vec<u8> {
+                    let mut bytes = Vec::with_capacity(Self::SERIALIZED_LEN);
+                    for commitment in &self.commitments {
+                        bytes.extend_from_slice(&commitment.to_bytes());
+                    }
+                    $(bytes.extend_from_slice(&self.responses.$secret_var.to_bytes());)+
+                    bytes
+                }
+
+                pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                    if bytes.len() != Self::SERIALIZED_LEN {
+                        return None;
+                    }
+                    let (commitment_bytes, response_bytes) = bytes.split_at(NUM_STATEMENTS * POINT_LEN);
+                    let commitments = sigma_proof::points_from_bytes(commitment_bytes)?;
+                    let mut responses = sigma_proof::scalars_from_bytes(response_bytes)?.into_iter();
+                    let responses = Responses {
+                        $($secret_var: responses.next()?,)+
+                    };
+                    Some(Self { commitments, responses })
+                }
+            }
+
+            fn challenge(sid: &str, pid: u64, assignments: &VerifyAssignments, commitments: &[ProjectivePoint]) -> Scalar {
+                let mut points = vec![$(assignments.$common_var.point(),)* $(*assignments.$instance_var,)*];
+                points.extend_from_slice(commitments);
+                sigma_proof::challenge(sid, pid, &points, LABEL)
+            }
+
+            $crate::__std_only! {
+                /// Prove knowledge of the secrets, without revealing them
+                pub fn prove(sid: &str, pid: u64, assignments: ProveAssignments) -> Proof {
+                    prove_with_rng(sid, pid, assignments, &mut sigma_proof::thread_rng())
+                }
+
+                /// Verify many proofs at once, see [`batch_verify_with_rng`]
+                pub fn batch_verify(proofs: &[Proof], sid: &str, pids: &[u64], assignments: BatchVerifyAssignments) -> bool {
+                    batch_verify_with_rng(proofs, sid, pids, assignments, &mut sigma_proof::thread_rng())
+                }
+            }
+
+            /// Prove knowledge of the secrets, without revealing them, with randomness from the caller's RNG
+            pub fn prove_with_rng(
+                sid: &str,
+                pid: u64,
+                assignments: ProveAssignments,
+                rng: &mut (impl sigma_proof::RngCore + sigma_proof::CryptoRng),
+            ) -> Proof {
+                $(let $instance_var = assignments.$instance_var;)*
+                $(let $common_var = assignments.$common_var;)*
+                let nonces = Responses {
+                    $($secret_var: sigma_proof::random_scalar(rng),)+
+                };
+                let commitments = vec![$( $first_point.mul_scalar(&nonces.$first_scalar) $(+ $point.mul_scalar(&nonces.$scalar))* ),+];
+
+                let public = VerifyAssignments {
+                    $($instance_var,)*
+                    $($common_var,)*
+                };
+                let c = challenge(sid, pid, &public, &commitments);
+                let responses = Responses {
+                    $($secret_var: nonces.$secret_var + c * assignments.$secret_var,)+
+                };
+                Proof { commitments, responses }
+            }
+
+            /// Verify that the prover knows secrets satisfying every statement
+            pub fn verify(proof: &Proof, sid: &str, pid: u64, assignments: VerifyAssignments) -> bool {
+                if proof.commitments.len() != NUM_STATEMENTS {
+                    return false;
+                }
+                let c = challenge(sid, pid, &assignments, &proof.commitments);
+                $(let $instance_var = assignments.$instance_var;)*
+                $(let $common_var = assignments.$common_var;)*
+                let responses = &proof.responses;
+                let mut commitments = proof.commitments.iter();
+                true $(&& {
+                    let t = commitments.next().unwrap();
+                    $first_point.mul_scalar(&responses.$first_scalar) $(+ $point.mul_scalar(&responses.$scalar))* == t + &(*$lhs * c)
+                })+
+            }
+
+            /// Verify many proofs at once, faster than verifying them one by one:
+            /// every verification equation is multiplied by a random weight w, and all of them are added into a single check.
+            /// The weighted responses multiplying a common point are summed over the batch, so that each common point is multiplied once,
+            /// and the terms w * T and (w * c) * L of all the equations are computed by one multiscalar multiplication, sharing its doublings
+            /// (instance points multiplied by a secret still cost one multiplication each)
+            ///
+            /// The proofs share the session id, `pids[k]` is the party id of `proofs[k]`
+            pub fn batch_verify_with_rng(
+                proofs: &[Proof],
+                sid: &str,
+                pids: &[u64],
+                assignments: BatchVerifyAssignments,
+                rng: &mut (impl sigma_proof::RngCore + sigma_proof::CryptoRng),
+            ) -> bool {
+                let batch_size = proofs.len();
+                if pids.len() != batch_size $(|| assignments.$instance_var.len() != batch_size)* {
+                    return false;
+                }
+                $(let $common_var = sigma_proof::FoldedBase::new(assignments.$common_var);)*
+                let mut sum = ProjectivePoint::IDENTITY;
+                let mut points_and_scalars = Vec::with_capacity(2 * NUM_STATEMENTS * batch_size);
+                for (k, (proof, pid)) in proofs.iter().zip(pids).enumerate() {
+                    if proof.commitments.len() != NUM_STATEMENTS {
+                        return false;
+                    }
+                    $(let $instance_var = &assignments.$instance_var[k];)*
+                    let public = VerifyAssignments {
+                        $($instance_var,)*
+                        $($common_var: assignments.$common_var,)*
+                    };
+                    let c = challenge(sid, *pid, &public, &proof.commitments);
+                    let responses = &proof.responses;
+                    let mut commitments = proof.commitments.iter();
+                    $(
+                        let t = commitments.next().unwrap();
+                        let weight = sigma_proof::random_scalar(rng);
+                        sum += $first_point.mul_scalar(&(weight * responses.$first_scalar)) $(+ $point.mul_scalar(&(weight * responses.$scalar)))*;
+                        points_and_scalars.push((*t, -weight));
+                        points_and_scalars.push((*$lhs, -(c * weight)));
+                    )+
+                }
+                $(sum += $common_var.finish();)*
+                sum += sigma_proof::multiscalar_mul(&points_and_scalars);
+                sum == ProjectivePoint::IDENTITY
+            }
+        }
+    };
+}
+
+/// The challenge of a proof generated by [`define_proof!`](crate::define_proof)
+pub fn challenge(sid: &str, pid: u64, points: &[ProjectivePoint], label: &str) -> Scalar {
+    transcript::challenge(sid.as_bytes(), pid, points, label.as_bytes())
+}
+
+pub fn random_scalar(rng: &mut (impl RngCore + CryptoRng)) -> Scalar {
+    Scalar::random(rng)
+}
+
+/// Σ k_i * P_i, sharing the doublings of the multiplications.
+/// By chunks, as k256 allocates a lookup table per point
+pub fn multiscalar_mul(points_and_scalars: &[(ProjectivePoint, Scalar)]) -> ProjectivePoint {
+    points_and_scalars
+        .chunks(128)
+        .map(ProjectivePoint::lincomb_ext)
+        .sum()
+}
+
+/// A proof whose number of commitments isn't its number of statements
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("a proof must have one commitment per statement")]
+pub struct InvalidProofLength;
+
+/// A common point of a batch verification: the weighted responses multiplying it are summed, and multiplied by it once
+#[doc(hidden)]
+pub struct FoldedBase<'a> {
+    base: &'a dyn BasePoint,
+    scalar: Cell<Scalar>,
+}
+impl<'a> FoldedBase<'a> {
+    pub fn new(base: &'a dyn BasePoint) -> Self {
+        Self {
+            base,
+            scalar: Cell::new(Scalar::ZERO),
+        }
+    }
+
+    /// Deferred to [`FoldedBase::finish`]: the same signature as [`BasePoint::mul_scalar`], for the code generated by `define_proof!`
+    pub fn mul_scalar(&self, scalar: &Scalar) -> ProjectivePoint {
+        self.scalar.set(self.scalar.get() + scalar);
+        ProjectivePoint::IDENTITY
+    }
+
+    /// The sum of the weighted responses, times the point
+    pub fn finish(&self) -> ProjectivePoint {
+        self.base.mul_scalar(&self.scalar.get())
+    }
+}
+
+/// Expands its items only when this crate is built with the `std` feature.
+/// `#[cfg(feature = "std")]` written in `define_proof!` would check the features of the crate calling the macro instead
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __std_only {
+    ($($item:item)*) => { $($item)* };
+}
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __std_only {
+    ($($item:item)*) => {};
+}
+
+pub fn points_from_bytes(bytes: &[u8]) -> Option<Vec<ProjectivePoint>> {
+    bytes
+        .chunks_exact(POINT_LEN)
+        .map(encoding::point_from_bytes)
+        .collect()
+}
+
+pub fn scalars_from_bytes(bytes: &[u8]) -> Option<Vec<Scalar>> {
+    bytes
+        .chunks_exact(SCALAR_LEN)
+        .map(encoding::scalar_from_bytes)
+        .collect()
+}
+
+pub use encoding::serde_points;
+
+crate::define_proof! {
+    /// `DLogProof` expressed with [`define_proof!`]: knowledge of x so that Y = x * B
+    ///
+    /// The challenge hashes the same points as `DLogProof` ([B, Y, T]), so with B = G both produce and accept the same proofs
+    dlog, "", (x), (Y), (B) : Y = (x * B)
+}
+
+impl From<&crate::DLogProof> for dlog::Proof {
+    fn from(proof: &crate::DLogProof) -> Self {
+        dlog::Proof::from_parts(vec![proof.t], dlog::Responses { x: proof.s })
+            .expect("dlog has one statement")
+    }
+}
+
+impl TryFrom<&dlog::Proof> for crate::DLogProof {
+    type Error = InvalidProofLength;
+
+    fn try_from(proof: &dlog::Proof) -> Result<Self, Self::Error> {
+        let [t] = proof.commitments() else {
+            return Err(InvalidProofLength);
+        };
+        Ok(Self {
+            t: *t,
+            s: proof.responses().x,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prepared_base::PreparedBase;
+    use crate::range_proof::PedersenGens;
+    use crate::{DLogProof, GENERATOR};
+    use k256::elliptic_curve::ops::MulByGenerator;
+    use rand::thread_rng;
+
+    crate::define_proof! {
+        dleq, "dleq", (x), (A, B), (G, H) : A = (x * G), B = (x * H)
+    }
+
+    crate::define_proof! {
+        pedersen_opening, "pedersen-opening", (x, r), (X, Y), (G, H, B) : X = (x * G), Y = (x * H + r * B)
+    }
+
+    #[test]
+    fn dlog_instance_interoperates_with_dlog_proof() {
+        let x = random_scalar(&mut thread_rng());
+        let y = ProjectivePoint::mul_by_generator(&x);
+
+        let proof = DLogProof::prove("sid", 1, x, y, GENERATOR);
+        let as_macro_proof = dlog::Proof::from(&proof);
+        let public = dlog::VerifyAssignments {
+            Y: &y,
+            B: &GENERATOR,
+        };
+        assert!(dlog::verify(&as_macro_proof, "sid", 1, public));
+
+        let macro_proof = dlog::prove(
+            "sid",
+            1,
+            dlog::ProveAssignments {
+                x: &x,
+                Y: &y,
+                B: &GENERATOR,
+            },
+        );
+        assert!(DLogProof::try_from(&macro_proof)
+            .unwrap()
+            .verify("sid", 1, y, GENERATOR));
+    }
+
+    #[test]
+    fn dleq_proof() {
+        let h = PedersenGens::default().b_blinding;
+        let x = random_scalar(&mut thread_rng());
+        let (a, b) = (GENERATOR * x, h * x);
+
+        let proof = dleq::prove(
+            "sid",
+            1,
+            dleq::ProveAssignments {
+                x: &x,
+                A: &a,
+                B: &b,
+                G: &GENERATOR,
+                H: &h,
+            },
+        );
+        let public = dleq::VerifyAssignments {
+            A: &a,
+            B: &b,
+            G: &GENERATOR,
+            H: &h,
+        };
+        assert!(dleq::verify(&proof, "sid", 1, public));
+        assert!(!dleq::verify(&proof, "sid", 2, public));
+
+        // B with a different discrete log than A
+        let wrong_b = h * random_scalar(&mut thread_rng());
+        let proof = dleq::prove(
+            "sid",
+            1,
+            dleq::ProveAssignments {
+                x: &x,
+                A: &a,
+                B: &wrong_b,
+                G: &GENERATOR,
+                H: &h,
+            },
+        );
+        let public = dleq::VerifyAssignments {
+            B: &wrong_b,
+            ..public
+        };
+        assert!(!dleq::verify(&proof, "sid", 1, public));
+    }
+
+    #[test]
+    fn batch_verify_pedersen_openings() {
+        let PedersenGens { b_blinding: h, .. } = PedersenGens::default();
+        let b = crate::transcript::hash_to_point(b"test", b"B");
+        let pids: Vec<u64> = (0..5).collect();
+
+        // the proofs are made with plain points, and batch verified with prepared ones: both hash and multiply the same points
+        let (prepared_h, prepared_b) = (PreparedBase::new(h), PreparedBase::new(b));
+
+        let mut proofs = Vec::new();
+        let mut assignments = pedersen_opening::BatchVerifyAssignments {
+            X: Vec::new(),
+            Y: Vec::new(),
+            G: &GENERATOR,
+            H: &prepared_h,
+            B: &prepared_b,
+        };
+        for pid in &pids {
+            let (x, r) = (
+                random_scalar(&mut thread_rng()),
+                random_scalar(&mut thread_rng()),
+            );
+            let (big_x, big_y) = (GENERATOR * x, h * x + b * r);
+            proofs.push(pedersen_opening::prove(
+                "sid",
+                *pid,
+                pedersen_opening::ProveAssignments {
+                    x: &x,
+                    r: &r,
+                    X: &big_x,
+                    Y: &big_y,
+                    G: &GENERATOR,
+                    H: &h,
+                    B: &b,
+                },
+            ));
+            assignments.X.push(big_x);
+            assignments.Y.push(big_y);
+        }
+        assert!(pedersen_opening::batch_verify(
+            &proofs,
+            "sid",
+            &pids,
+            assignments.clone()
+        ));
+
+        // one bad instance makes the whole batch fail
+        let mut bad_assignments = assignments.clone();
+        bad_assignments.Y[3] += GENERATOR;
+        assert!(!pedersen_opening::batch_verify(
+            &proofs,
+            "sid",
+            &pids,
+            bad_assignments
+        ));
+        assert!(!pedersen_opening::batch_verify(
+            &proofs[1..],
+            "sid",
+            &pids,
+            assignments.clone()
+        ));
+
+        // one bad response to a common point, whose terms are summed over the batch
+        let mut bad_proofs = proofs.clone();
+        let responses = pedersen_opening::Responses {
+            r: proofs[2].responses().r + Scalar::ONE,
+            ..*proofs[2].responses()
+        };
+        bad_proofs[2] =
+            pedersen_opening::Proof::from_parts(proofs[2].commitments().to_vec(), responses)
+                .unwrap();
+        assert!(!pedersen_opening::batch_verify(
+            &bad_proofs,
+            "sid",
+            &pids,
+            assignments
+        ));
+    }
+
+    crate::define_proof! {
+        /// An instance point P multiplied by the secret, instead of a common point
+        same_key, "same-key", (x), (A, B, P), (G) : A = (x * G), B = (x * P)
+    }
+
+    #[test]
+    fn batch_verify_instance_points() {
+        let pids: Vec<u64> = (0..4).collect();
+        let mut proofs = Vec::new();
+        let mut assignments = same_key::BatchVerifyAssignments {
+            A: Vec::new(),
+            B: Vec::new(),
+            P: Vec::new(),
+            G: &GENERATOR,
+        };
+        for pid in &pids {
+            let x = random_scalar(&mut thread_rng());
+            let p = GENERATOR * random_scalar(&mut thread_rng());
+            let (a, b) = (GENERATOR * x, p * x);
+            proofs.push(same_key::prove(
+                "sid",
+                *pid,
+                same_key::ProveAssignments {
+                    x: &x,
+                    A: &a,
+                    B: &b,
+                    P: &p,
+                    G: &GENERATOR,
+                },
+            ));
+            assignments.A.push(a);
+            assignments.B.push(b);
+            assignments.P.push(p);
+        }
+        assert!(same_key::batch_verify(
+            &proofs,
+            "sid",
+            &pids,
+            assignments.clone()
+        ));
+
+        assignments.B[1] += GENERATOR;
+        assert!(!same_key::batch_verify(&proofs, "sid", &pids, assignments));
+    }
+
+    #[test]
+    fn proof_serialization_roundtrip() {
+        let x = random_scalar(&mut thread_rng());
+        let y = GENERATOR * x;
+        let proof = dlog::prove(
+            "sid",
+            1,
+            dlog::ProveAssignments {
+                x: &x,
+                Y: &y,
+                B: &GENERATOR,
+            },
+        );
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), dlog::Proof::SERIALIZED_LEN);
+        assert_eq!(dlog::Proof::from_bytes(&bytes), Some(proof.clone()));
+        assert_eq!(dlog::Proof::from_bytes(&bytes[1..]), None);
+
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<dlog::Proof>(&json).unwrap(), proof);
+
+        // as many commitments as statements, checked by serde too
+        let mut value = serde_json::to_value(&proof).unwrap();
+        for commitments in [0, 2] {
+            let commitment = value["commitments"][0].clone();
+            value["commitments"] = serde_json::Value::Array(vec![commitment; commitments]);
+            assert!(serde_json::from_value::<dlog::Proof>(value.clone()).is_err());
+        }
+        assert_eq!(
+            DLogProof::try_from(&proof).unwrap(),
+            DLogProof::from_bytes(&bytes).unwrap()
+        );
+    }
+}
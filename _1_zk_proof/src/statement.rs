@@ -0,0 +1,140 @@
+use crate::prepared_base::BasePoint;
+use crate::GENERATOR;
+use alloc::string::String;
+use core::fmt;
+use k256::elliptic_curve::zeroize::Zeroize;
+use k256::{ProjectivePoint, Scalar};
+use rand::distributions::Alphanumeric;
+use rand::{CryptoRng, Rng, RngCore};
+
+/// Session id (sid): binds a proof to one session of the protocol, so that it can't be replayed in another one
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionId(String);
+impl SessionId {
+    pub fn new(sid: impl Into<String>) -> Self {
+        Self(sid.into())
+    }
+
+    /// A random 7-character alphanumeric session id
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut rand::thread_rng())
+    }
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        Self((0..7).map(|_| rng.sample(Alphanumeric) as char).collect())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+impl AsRef<[u8]> for SessionId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+impl From<&str> for SessionId {
+    fn from(sid: &str) -> Self {
+        Self::new(sid)
+    }
+}
+impl From<String> for SessionId {
+    fn from(sid: String) -> Self {
+        Self(sid)
+    }
+}
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Party id (pid): binds a proof to the party who made it, so that another party can't present it as their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PartyId(pub u64);
+impl From<u64> for PartyId {
+    fn from(pid: u64) -> Self {
+        Self(pid)
+    }
+}
+impl From<PartyId> for u64 {
+    fn from(pid: PartyId) -> Self {
+        pid.0
+    }
+}
+
+/// The public statement of a DLOG proof: y = x * base, for some x known by the prover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Statement {
+    pub base: ProjectivePoint,
+    pub y: ProjectivePoint,
+}
+impl Statement {
+    /// y = x * G
+    pub fn new(y: ProjectivePoint) -> Self {
+        Self { base: GENERATOR, y }
+    }
+
+    pub fn with_base(base: ProjectivePoint, y: ProjectivePoint) -> Self {
+        Self { base, y }
+    }
+}
+
+/// The secret x of a [`Statement`], known only by the prover
+///
+/// It is wiped from memory when dropped, and not printed by `Debug`
+pub struct Witness(Scalar);
+impl Witness {
+    pub fn new(x: Scalar) -> Self {
+        Self(x)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut rand::thread_rng())
+    }
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        Self(Scalar::generate_vartime(rng))
+    }
+
+    /// The statement y = x * base proven by this witness
+    pub fn statement(&self, base: ProjectivePoint) -> Statement {
+        Statement::with_base(base, base.mul_scalar(&self.0))
+    }
+
+    pub fn expose_secret(&self) -> &Scalar {
+        &self.0
+    }
+}
+impl Drop for Witness {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+impl fmt::Debug for Witness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Witness(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use rand::thread_rng;
+
+    #[test]
+    fn witness_debug_does_not_leak_secret() {
+        let witness = Witness::new(Scalar::from(42u64));
+        assert_eq!(format!("{witness:?}"), "Witness(<redacted>)");
+    }
+
+    #[test]
+    fn witness_statement_matches_secret() {
+        let witness = Witness::random_with_rng(&mut thread_rng());
+        assert_eq!(
+            witness.statement(GENERATOR),
+            Statement::new(GENERATOR * witness.expose_secret())
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use alloc::vec::Vec;
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::hash2curve::{ExpandMsgXmd, GroupDigest};
+use k256::elliptic_curve::ops::Reduce;
+use k256::{ProjectivePoint, Scalar, Secp256k1, U256};
+use sha2::{Digest, Sha256};
+
+/// Hash the public variables of a proof the same way `DLogProof` does:
+/// Sha256( sid || pid (little-endian) || points (compressed SEC1) || msg )
+///
+/// `msg` is appended last, so hashing with an empty `msg` gives exactly the `DLogProof` hash
+pub(crate) fn hash_points(sid: &[u8], pid: u64, points: &[ProjectivePoint], msg: &[u8]) -> U256 {
+    let mut hasher = Sha256::new();
+    hasher.update(sid);
+    hasher.update(pid.to_le_bytes());
+    for point in points {
+        hasher.update(point.to_bytes());
+    }
+    hasher.update(msg);
+    let hash = hasher.finalize();
+
+    // Sha256 hash size is 256 bits, or 32 bytes.
+    U256::from_be_slice(&hash)
+}
+
+/// Compute a (deterministic) Fiat-Shamir challenge from the public variables, reduced modulo the curve order
+pub(crate) fn challenge(sid: &[u8], pid: u64, points: &[ProjectivePoint], msg: &[u8]) -> Scalar {
+    Scalar::reduce(hash_points(sid, pid, points, msg))
+}
+
+/// Hash arbitrary bytes to a curve point nobody knows the discrete log of (RFC 9380, secp256k1_XMD:SHA-256_SSWU_RO_)
+///
+/// `dst` is the domain separation tag, so that different protocols never derive the same points
+pub(crate) fn hash_to_point(dst: &[u8], msg: &[u8]) -> ProjectivePoint {
+    // only fails if dst is longer than 255 bytes, which our constant tags never are
+    Secp256k1::hash_from_bytes::<ExpandMsgXmd<Sha256>>(&[msg], &[dst])
+        .expect("domain separation tag must be at most 255 bytes")
+}
+
+/// Running Fiat-Shamir transcript for multi-round proofs
+///
+/// Every challenge is `challenge(sid, pid, points, msg)` over everything appended so far, so each challenge commits to the whole proof up to that point
+pub(crate) struct Transcript<'a> {
+    sid: &'a [u8],
+    pid: u64,
+    points: Vec<ProjectivePoint>,
+    msg: Vec<u8>,
+}
+impl<'a> Transcript<'a> {
+    /// `label` separates the protocols using the transcript
+    pub(crate) fn new(sid: &'a [u8], pid: u64, label: &[u8]) -> Self {
+        Self {
+            sid,
+            pid,
+            points: Vec::new(),
+            msg: label.to_vec(),
+        }
+    }
+
+    pub(crate) fn append_point(&mut self, point: &ProjectivePoint) {
+        self.points.push(*point);
+    }
+
+    pub(crate) fn append_scalar(&mut self, scalar: &Scalar) {
+        self.msg.extend_from_slice(&scalar.to_bytes());
+    }
+
+    pub(crate) fn append_u64(&mut self, n: u64) {
+        self.msg.extend_from_slice(&n.to_le_bytes());
+    }
+
+    pub(crate) fn challenge(&mut self) -> Scalar {
+        let c = challenge(self.sid, self.pid, &self.points, &self.msg);
+        // make successive challenges distinct even when nothing was appended in between
+        self.append_scalar(&c);
+        c
+    }
+}
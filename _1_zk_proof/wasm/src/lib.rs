@@ -0,0 +1,150 @@
+//! WebAssembly bindings for the DLOG proof of `_1_zk_proof`
+//!
+//! Points are passed as hex of their compressed SEC1 encoding (33 bytes), scalars as hex of their big-endian encoding (32 bytes).
+//! The base point is always the secp256k1 generator G.
+use _1_zk_proof::encoding::{point_to_hex, scalar_to_hex};
+use _1_zk_proof::{encoding, GENERATOR};
+use k256::elliptic_curve::ops::MulByGenerator;
+use k256::{ProjectivePoint, Scalar};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// [`encoding::point_from_hex`], naming the invalid argument
+fn point_from_hex(name: &str, point: &str) -> Result<ProjectivePoint, JsError> {
+    encoding::point_from_hex(point).ok_or_else(|| {
+        JsError::new(&format!(
+            "{name} is not a hex-encoded compressed curve point"
+        ))
+    })
+}
+
+/// [`encoding::scalar_from_hex`], naming the invalid argument
+fn scalar_from_hex(name: &str, scalar: &str) -> Result<Scalar, JsError> {
+    encoding::scalar_from_hex(scalar)
+        .ok_or_else(|| JsError::new(&format!("{name} is not a hex-encoded scalar")))
+}
+
+/// A secret key x, and its public key Y = xG
+#[wasm_bindgen]
+pub struct KeyPair {
+    x: Scalar,
+    y: ProjectivePoint,
+}
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> String {
+        scalar_to_hex(&self.x)
+    }
+
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> String {
+        point_to_hex(&self.y)
+    }
+}
+
+/// Generate a random key pair, with randomness from the JS crypto API
+#[wasm_bindgen(js_name = generateKeyPair)]
+pub fn generate_key_pair() -> KeyPair {
+    let x = Scalar::generate_vartime(&mut rand::thread_rng());
+    let y = ProjectivePoint::mul_by_generator(&x);
+    KeyPair { x, y }
+}
+
+/// Get the public key Y = xG of a secret key x
+#[wasm_bindgen(js_name = publicKeyFromSecret)]
+pub fn public_key_from_secret(secret_key: &str) -> Result<String, JsError> {
+    let x = scalar_from_hex("secretKey", secret_key)?;
+    Ok(point_to_hex(&ProjectivePoint::mul_by_generator(&x)))
+}
+
+/// A random problem instance: session id, party id, and public point y
+#[wasm_bindgen]
+pub struct Problem(_1_zk_proof::Problem);
+#[wasm_bindgen]
+impl Problem {
+    pub fn random() -> Self {
+        let (problem, _) = _1_zk_proof::Problem::random();
+        Self(problem)
+    }
+
+    /// A problem with a random sid and pid, whose public point is y = xG
+    #[wasm_bindgen(js_name = fromSolution)]
+    pub fn from_solution(secret_key: &str) -> Result<Problem, JsError> {
+        let x = scalar_from_hex("secretKey", secret_key)?;
+        Ok(Self(_1_zk_proof::Problem::from_solution(x)))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sid(&self) -> String {
+        self.0.sid.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pid(&self) -> u64 {
+        self.0.pid.0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> String {
+        point_to_hex(&self.0.statement.y)
+    }
+}
+
+/// JSON form of a proof: the hex of the commitment T = rG and of the response s = r + c * x
+#[derive(Serialize, Deserialize)]
+struct DLogProofJson {
+    t: String,
+    s: String,
+}
+
+/// Non-interactive Schnorr proof of knowledge of x so that y = xG, see `_1_zk_proof::DLogProof`
+#[wasm_bindgen]
+pub struct DLogProof(_1_zk_proof::DLogProof);
+#[wasm_bindgen]
+impl DLogProof {
+    /// Prove the knowledge of the secret key x of the public key y
+    pub fn prove(sid: &str, pid: u64, x: &str, y: &str) -> Result<DLogProof, JsError> {
+        let x = scalar_from_hex("x", x)?;
+        let y = point_from_hex("y", y)?;
+        Ok(Self(_1_zk_proof::DLogProof::prove(
+            sid, pid, x, y, GENERATOR,
+        )))
+    }
+
+    pub fn verify(&self, sid: &str, pid: u64, y: &str) -> Result<bool, JsError> {
+        let y = point_from_hex("y", y)?;
+        Ok(self.0.verify(sid, pid, y, GENERATOR))
+    }
+
+    /// Hex of T || s (65 bytes)
+    #[wasm_bindgen(js_name = toHex)]
+    pub fn to_hex(&self) -> String {
+        self.0.to_hex()
+    }
+
+    #[wasm_bindgen(js_name = fromHex)]
+    pub fn from_hex(proof: &str) -> Result<DLogProof, JsError> {
+        _1_zk_proof::DLogProof::from_hex(proof)
+            .map(Self)
+            .ok_or_else(|| JsError::new("proof is not a hex-encoded DLOG proof"))
+    }
+
+    /// JSON text `{"t": hex of the commitment T = rG, "s": hex of the response s = r + c * x}`
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        let json = DLogProofJson {
+            t: point_to_hex(self.0.t()),
+            s: scalar_to_hex(self.0.s()),
+        };
+        serde_json::to_string(&json).expect("strings serialize to JSON")
+    }
+
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<DLogProof, JsError> {
+        let json: DLogProofJson = serde_json::from_str(json)?;
+        let t = point_from_hex("t", &json.t)?;
+        let s = scalar_from_hex("s", &json.s)?;
+        Ok(Self(_1_zk_proof::DLogProof::from_parts(t, s)))
+    }
+}
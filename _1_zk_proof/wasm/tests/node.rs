@@ -0,0 +1,57 @@
+//! Test suite for Node (the default runner of wasm-bindgen-test, no browser needed)
+#![cfg(target_arch = "wasm32")]
+
+use _1_zk_proof_wasm::{generate_key_pair, public_key_from_secret, DLogProof, Problem};
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen_test]
+fn prove_verify_ok() {
+    let key_pair = generate_key_pair();
+    let problem = Problem::from_solution(&key_pair.secret_key()).unwrap();
+    assert_eq!(problem.y(), key_pair.public_key());
+
+    let proof = DLogProof::prove(
+        &problem.sid(),
+        problem.pid(),
+        &key_pair.secret_key(),
+        &problem.y(),
+    )
+    .unwrap();
+    assert!(proof
+        .verify(&problem.sid(), problem.pid(), &problem.y())
+        .unwrap());
+    assert!(!proof
+        .verify("other sid", problem.pid(), &problem.y())
+        .unwrap());
+    assert!(!proof
+        .verify(&problem.sid(), problem.pid() + 1, &problem.y())
+        .unwrap());
+}
+
+#[wasm_bindgen_test]
+fn proof_hex_and_json_roundtrip() {
+    let key_pair = generate_key_pair();
+    let y = public_key_from_secret(&key_pair.secret_key()).unwrap();
+    let proof = DLogProof::prove("sid", 1, &key_pair.secret_key(), &y).unwrap();
+
+    let hex = proof.to_hex();
+    assert_eq!(hex.len(), 2 * 65);
+    let from_hex = DLogProof::from_hex(&hex).unwrap();
+    assert!(from_hex.verify("sid", 1, &y).unwrap());
+
+    let from_json = DLogProof::from_json(&proof.to_json()).unwrap();
+    assert_eq!(from_json.to_hex(), hex);
+}
+
+#[wasm_bindgen_test]
+fn invalid_inputs_are_rejected() {
+    let key_pair = generate_key_pair();
+    assert!(DLogProof::prove("sid", 1, "not hex", &key_pair.public_key()).is_err());
+    assert!(DLogProof::prove("sid", 1, &key_pair.secret_key(), "02ab").is_err());
+    assert!(DLogProof::from_hex("00").is_err());
+    assert!(DLogProof::from_json(r#"{"t": "02"}"#).is_err());
+
+    let random = Problem::random();
+    let proof = DLogProof::prove("sid", 1, &key_pair.secret_key(), &key_pair.public_key()).unwrap();
+    assert!(!proof.verify("sid", 1, &random.y()).unwrap());
+}
@@ -0,0 +1,255 @@
+//! A WebSocket echo server, for the tests and the demo UI of `_2_websocket_wasm`, without internet.
+//!
+//! It echoes text and binary messages, after an optional greeting banner. For fault injection, it can delay its replies,
+//! send ping frames, and close connections with a given code. Each connection can override the defaults in its query string,
+//! e.g. `ws://127.0.0.1:9001/?delay_ms=500&close_after=1&close_code=4000`
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Interval;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+
+/// Where the tests and the demo UI expect the server
+pub const DEFAULT_ADDR: &str = "127.0.0.1:9001";
+pub const DEFAULT_BANNER: &str = "Welcome to the echo server";
+
+/// How connections are served
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Sent to each connection before any echo
+    pub banner: Option<String>,
+    /// Before each echo
+    pub delay_ms: u64,
+    /// Close connections after echoing that many messages, right after the banner with 0
+    pub close_after: Option<u32>,
+    /// 1006 drops the connection without a closing handshake
+    pub close_code: u16,
+    pub close_reason: String,
+    /// Send ping frames at that interval
+    pub ping_interval_ms: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            banner: Some(DEFAULT_BANNER.to_string()),
+            delay_ms: 0,
+            close_after: None,
+            close_code: 1000,
+            close_reason: String::new(),
+            ping_interval_ms: None,
+        }
+    }
+}
+
+impl Config {
+    /// Set the field named `key`. An empty banner disables it
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("{key} must be a number, got {value:?}"))
+        }
+        match key {
+            "banner" => self.banner = Some(value.to_string()).filter(|banner| !banner.is_empty()),
+            "delay_ms" => self.delay_ms = number(key, value)?,
+            "close_after" => self.close_after = Some(number(key, value)?),
+            "close_code" => self.close_code = number(key, value)?,
+            "close_reason" => self.close_reason = value.to_string(),
+            "ping_interval_ms" => self.ping_interval_ms = Some(number(key, value)?),
+            _ => return Err(format!("unknown setting {key:?}")),
+        }
+        Ok(())
+    }
+
+    /// This config, overridden by the `key=value` pairs of a URL query string
+    pub fn with_query(&self, query: &str) -> Result<Config, String> {
+        let mut config = self.clone();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            config.set(&percent_decode(key), &percent_decode(value))?;
+        }
+        Ok(config)
+    }
+}
+
+/// Decodes `%XX` escapes and `+` for spaces, leaving invalid escapes as they are
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            let hex = std::str::from_utf8(hex).ok()?;
+            u8::from_str_radix(hex, 16).ok()
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Serve the connections of `listener` until it fails
+pub async fn serve(listener: TcpListener, config: Config) {
+    loop {
+        let (tcp, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                eprintln!("accept failed: {err}");
+                return;
+            }
+        };
+        let config = config.clone();
+        tokio::spawn(async move {
+            match handle(tcp, &config).await {
+                Ok(()) | Err(tungstenite::Error::ConnectionClosed) => {}
+                Err(err) => eprintln!("{peer}: {err}"),
+            }
+        });
+    }
+}
+
+/// Serve on a free port of localhost, in a task of the current runtime
+pub async fn spawn(config: Config) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(serve(listener, config));
+    Ok(addr)
+}
+
+async fn handle(tcp: TcpStream, defaults: &Config) -> Result<(), tungstenite::Error> {
+    let mut config = defaults.clone();
+    // the error type is tungstenite's
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| match defaults
+        .with_query(request.uri().query().unwrap_or_default())
+    {
+        Ok(overridden) => {
+            config = overridden;
+            Ok(response)
+        }
+        Err(err) => {
+            let mut response = ErrorResponse::new(Some(err));
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+            Err(response)
+        }
+    };
+    let mut ws = tokio_tungstenite::accept_hdr_async(tcp, callback).await?;
+
+    if let Some(banner) = &config.banner {
+        ws.send(Message::text(banner)).await?;
+    }
+    if config.close_after == Some(0) {
+        return close(ws, &config).await;
+    }
+    let mut pings = config
+        .ping_interval_ms
+        .map(|ms| tokio::time::interval(Duration::from_millis(ms)));
+    let mut echoed = 0;
+    loop {
+        let msg = tokio::select! {
+            msg = ws.next() => msg,
+            _ = tick(&mut pings) => {
+                ws.send(Message::Ping(Vec::new())).await?;
+                continue;
+            }
+        };
+        match msg {
+            Some(Ok(msg @ (Message::Text(_) | Message::Binary(_)))) => {
+                tokio::time::sleep(Duration::from_millis(config.delay_ms)).await;
+                ws.send(msg).await?;
+                echoed += 1;
+                if config.close_after == Some(echoed) {
+                    return close(ws, &config).await;
+                }
+            }
+            // pings and closing handshakes are answered by tungstenite
+            Some(Ok(_)) => {}
+            Some(Err(err)) => return Err(err),
+            None => return Ok(()),
+        }
+    }
+}
+
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn close(
+    mut ws: WebSocketStream<TcpStream>,
+    config: &Config,
+) -> Result<(), tungstenite::Error> {
+    // dropping the TCP stream
+    if config.close_code == 1006 {
+        return Ok(());
+    }
+    let frame = CloseFrame {
+        code: config.close_code.into(),
+        reason: config.close_reason.clone().into(),
+    };
+    ws.close(Some(frame)).await?;
+    // until the client's close frame
+    while ws.next().await.transpose()?.is_some() {}
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_overrides_the_defaults() {
+        let defaults = Config::default();
+        assert_eq!(defaults.with_query("").unwrap(), defaults);
+
+        let config = defaults
+            .with_query("banner=&delay_ms=20&close_after=1&close_code=4000&close_reason=bye%20now")
+            .unwrap();
+        let expected = Config {
+            banner: None,
+            delay_ms: 20,
+            close_after: Some(1),
+            close_code: 4000,
+            close_reason: "bye now".to_string(),
+            ping_interval_ms: None,
+        };
+        assert_eq!(config, expected);
+
+        let config = defaults.with_query("banner=hi+there%21").unwrap();
+        assert_eq!(config.banner.as_deref(), Some("hi there!"));
+
+        assert!(defaults.with_query("delay_ms=soon").is_err());
+        assert!(defaults.with_query("color=blue").is_err());
+    }
+
+    #[test]
+    fn invalid_escapes_are_kept() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%C3%A9t%C3%A9"), "été");
+    }
+}
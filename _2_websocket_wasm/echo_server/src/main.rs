@@ -0,0 +1,44 @@
+use std::process::exit;
+
+use _2_echo_server::{serve, Config, DEFAULT_ADDR};
+use tokio::net::TcpListener;
+
+const USAGE: &str = "Usage: echo_server [--addr HOST:PORT] [--banner TEXT] [--delay-ms MS] [--close-after N] [--close-code CODE] [--close-reason TEXT] [--ping-interval-ms MS]
+
+A WebSocket echo server, on 127.0.0.1:9001 by default. Each connection can override the settings in its query string,
+e.g. ws://127.0.0.1:9001/?banner=&delay_ms=500&close_after=1&close_code=4000";
+
+/// Parse the arguments, then serve until killed
+#[tokio::main]
+async fn main() {
+    let mut addr = DEFAULT_ADDR.to_string();
+    let mut config = Config::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let (Some(key), Some(value)) = (arg.strip_prefix("--"), args.next()) else {
+            println!("{USAGE}");
+            exit(if arg == "--help" { 0 } else { 2 });
+        };
+        let set = match key {
+            "addr" => {
+                addr = value;
+                Ok(())
+            }
+            key => config.set(&key.replace('-', "_"), &value),
+        };
+        if let Err(err) = set {
+            eprintln!("{err}\n\n{USAGE}");
+            exit(2);
+        }
+    }
+
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("cannot listen on {addr}: {err}");
+            exit(1);
+        }
+    };
+    println!("echo server listening on ws://{addr}");
+    serve(listener, config).await;
+}
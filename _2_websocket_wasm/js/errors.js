@@ -0,0 +1,43 @@
+// Errors thrown by the wasm module, built from `WsError` (src/web/error.rs)
+
+export class WsError extends Error {
+  constructor(message, code) {
+    super(message);
+    this.code = code;
+  }
+}
+export class InvalidUrlError extends WsError {}
+export class ConnectError extends WsError {}
+export class ClosedError extends WsError {
+  constructor(message, code, closeCode, reason) {
+    super(message, code);
+    this.closeCode = closeCode;
+    this.reason = reason;
+  }
+}
+export class TimeoutError extends WsError {}
+export class AbortError extends WsError {}
+export class UnexpectedMessageTypeError extends WsError {}
+export class InvalidArgumentError extends WsError {}
+
+const classes = {
+  WsError,
+  InvalidUrlError,
+  ConnectError,
+  ClosedError,
+  TimeoutError,
+  AbortError,
+  UnexpectedMessageTypeError,
+  InvalidArgumentError,
+};
+
+export function createError(className, message, code, closeCode, reason) {
+  const err = new classes[className](message, code, closeCode, reason);
+  // set explicitly: class names don't survive minifiers
+  err.name = className;
+  return err;
+}
+
+export function errorClasses() {
+  return classes;
+}
@@ -3,14 +3,15 @@ import { render } from "solid-js/web";
 import { Component, createSignal } from "solid-js";
 // @ts-ignore
 import init from "my-wasm-web?init";
-import { WS_URL, wsPing } from "./ws_ping";
+import { WS_URL, wsRequest } from "./ws_ping";
 
 const App: Component = () => {
   const [respTxt, setRespTxt] = createSignal<string | null>(null);
 
   async function handle_websocket() {
     await init();
-    let got_from_rust = await wsPing(WS_URL, "Hello, world!");
+    // the echo, not the greeting banner the server sends first
+    let got_from_rust = (await wsRequest(WS_URL, "Hello, world!", "echo")) as string;
     console.log({ got_from_rust });
     setRespTxt(got_from_rust);
   }
@@ -1,10 +1,22 @@
 // @ts-ignore
 import init, * as wasm from "my-wasm-web?init";
 
-export const WS_URL = "wss://echo.websocket.events";
+// the local echo server (`cargo run -p _2_echo_server`), unless VITE_WS_URL is set
+export const WS_URL: string = import.meta.env.VITE_WS_URL ?? "ws://127.0.0.1:9001";
 
-export function wsPing(endpoint: string, message: string): Promise<string> {
-  return wasm.ws_ping(endpoint, message);
+export function wsPing(endpoint: string, message: string, timeoutMs?: number, signal?: AbortSignal): Promise<string> {
+  return wasm.ws_ping(endpoint, message, timeoutMs, signal);
+}
+
+// resolves with the first reply the matcher accepts, skipping the others (e.g. a greeting banner)
+export function wsRequest(
+  endpoint: string,
+  message: string | Uint8Array,
+  matcher: "echo" | { jsonId: string } | ((reply: string | Uint8Array) => boolean),
+  timeoutMs?: number,
+  signal?: AbortSignal,
+): Promise<string | Uint8Array> {
+  return wasm.ws_request(endpoint, message, matcher, timeoutMs, signal);
 }
 
 export function wsPing_ts(endpoint: string, message: string): Promise<string> {
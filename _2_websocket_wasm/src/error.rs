@@ -0,0 +1,39 @@
+/// Errors of the WebSocket layer
+///
+/// In JS, each variant is an `Error` subclass of `WsError` with a stable `code`, see `ws_error_classes`
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum WsError {
+    #[error("invalid WebSocket URL {url}: {message}")]
+    InvalidUrl { url: String, message: String },
+    #[error("failed connecting to {url}")]
+    ConnectFailed { url: String },
+    #[error("connection closed: {code} {reason}")]
+    Closed { code: u16, reason: String },
+    #[error("no reply after {ms} ms")]
+    Timeout { ms: u32 },
+    #[error("aborted")]
+    Aborted,
+    #[error("message event: received unknown type")]
+    UnexpectedMessageType,
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    /// Any other exception thrown by the browser's `WebSocket`, or error of the native socket
+    #[error("{0}")]
+    Js(String),
+}
+
+impl WsError {
+    /// The `code` of the JS error
+    pub fn code(&self) -> &'static str {
+        match self {
+            WsError::InvalidUrl { .. } => "INVALID_URL",
+            WsError::ConnectFailed { .. } => "CONNECT_FAILED",
+            WsError::Closed { .. } => "CLOSED",
+            WsError::Timeout { .. } => "TIMEOUT",
+            WsError::Aborted => "ABORTED",
+            WsError::UnexpectedMessageType => "UNEXPECTED_MESSAGE_TYPE",
+            WsError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            WsError::Js(_) => "WEBSOCKET_ERROR",
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! The native backend, over `tokio-tungstenite`: the same client API as in browsers, run on tokio.
+//!
+//! Connections run in tasks spawned on the current tokio runtime.
+//! There is no `AbortSignal`: dropping a future cancels it
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use futures::future;
+
+use crate::{protocol, request, Message, WsError};
+
+mod client;
+mod reconnect;
+mod stream;
+pub use client::WsClient;
+pub use reconnect::ReconnectingWsClient;
+pub use stream::WsStream;
+
+/// Resolves with the first message received, fails with `WsError::Timeout` after `timeout_ms`
+pub async fn ws_ping(
+    endpoint: &str,
+    message: &str,
+    timeout_ms: Option<u32>,
+) -> Result<Message, WsError> {
+    ping(endpoint, Message::from(message), timeout_ms).await
+}
+
+/// Same as `ws_ping`, with a binary message
+pub async fn ws_ping_binary(
+    endpoint: &str,
+    message: &[u8],
+    timeout_ms: Option<u32>,
+) -> Result<Message, WsError> {
+    ping(endpoint, Message::from(message.to_vec()), timeout_ms).await
+}
+
+async fn ping(
+    endpoint: &str,
+    message: Message,
+    timeout_ms: Option<u32>,
+) -> Result<Message, WsError> {
+    let exchange = async {
+        let mut ws = WsStream::connect(endpoint).await?;
+        protocol::exchange(&mut ws, message).await
+    };
+    protocol::cancellable(exchange, timeout_ms, sleep, future::pending()).await
+}
+
+/// Which reply answers a `ws_request`, with a closure as predicate
+pub type Matcher = request::Matcher<Box<dyn FnMut(&Message) -> bool + Send>>;
+
+/// Resolves with the first message received that `matcher` accepts, skipping the others.
+/// Fails with `WsError::Timeout` after `timeout_ms`
+pub async fn ws_request(
+    endpoint: &str,
+    message: impl Into<Message>,
+    matcher: Matcher,
+    timeout_ms: Option<u32>,
+) -> Result<Message, WsError> {
+    let exchange = async {
+        let mut ws = WsStream::connect(endpoint).await?;
+        request::request(&mut ws, message.into(), matcher).await
+    };
+    protocol::cancellable(exchange, timeout_ms, sleep, future::pending()).await
+}
+
+fn sleep(ms: u32) -> tokio::time::Sleep {
+    tokio::time::sleep(Duration::from_millis(ms.into()))
+}
+
+/// Uniform in [0, 1), from the random keys of the std hash maps: good enough for jitter
+fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use _2_echo_server::{Config, DEFAULT_BANNER};
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::{ReconnectEvent, ReconnectOptions};
+
+    /// An in-process echo server, configured by the query string of each connection
+    async fn echo_server() -> String {
+        let addr = _2_echo_server::spawn(Config::default()).await.unwrap();
+        format!("ws://{}", addr)
+    }
+
+    /// Nothing listens there
+    async fn closed_port() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("ws://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn ping_resolves_with_the_first_reply() {
+        let url = format!("{}/?banner=", echo_server().await);
+        let reply = ws_ping(&url, "hello ws", None).await.unwrap();
+        assert_eq!(reply, Message::from("hello ws"));
+        let reply = ws_ping_binary(&url, &[1, 2, 3], Some(1000)).await.unwrap();
+        assert_eq!(reply, Message::Binary(vec![1, 2, 3]));
+
+        // the greeting banner comes first
+        let reply = ws_ping(&echo_server().await, "hello ws", None)
+            .await
+            .unwrap();
+        assert_eq!(reply, Message::from(DEFAULT_BANNER));
+    }
+
+    #[tokio::test]
+    async fn requests_skip_unrelated_messages() {
+        let url = echo_server().await;
+        let reply = ws_request(&url, "hello ws", Matcher::Echo, None).await;
+        assert_eq!(reply.unwrap(), Message::from("hello ws"));
+
+        let request = r#"{"id": 1, "method": "ping"}"#;
+        let reply = ws_request(&url, request, Matcher::JsonId("id".to_string()), None).await;
+        assert_eq!(reply.unwrap(), Message::from(request));
+
+        let binary = Matcher::Predicate(Box::new(|reply| matches!(reply, Message::Binary(_))));
+        let reply = ws_request(&url, vec![1, 2], binary, None).await;
+        assert_eq!(reply.unwrap(), Message::Binary(vec![1, 2]));
+
+        // the request itself fails the predicate: nothing else comes
+        let never = Matcher::Predicate(Box::new(|_| false));
+        let err = ws_request(&url, "hello ws", never, Some(50)).await;
+        assert_eq!(err.unwrap_err(), WsError::Timeout { ms: 50 });
+
+        let err = ws_request(&url, "hello ws", Matcher::JsonId("id".to_string()), None).await;
+        assert!(matches!(err, Err(WsError::InvalidArgument(_))), "{:?}", err);
+
+        // closed before the reply
+        let url = format!("{}/?close_after=0&close_code=4000", url);
+        let err = ws_request(&url, "hello ws", Matcher::Echo, None).await;
+        assert!(
+            matches!(err, Err(WsError::Closed { code: 4000, .. })),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn ping_times_out() {
+        let url = format!("{}/?banner=&delay_ms=1000", echo_server().await);
+        let err = ws_ping(&url, "hello ws", Some(50)).await.unwrap_err();
+        assert_eq!(err, WsError::Timeout { ms: 50 });
+    }
+
+    #[tokio::test]
+    async fn connection_errors() {
+        let err = ws_ping("not a url", "hello ws", None).await.unwrap_err();
+        assert!(matches!(err, WsError::InvalidUrl { .. }), "{:?}", err);
+
+        let url = closed_port().await;
+        let err = ws_ping(&url, "hello ws", None).await.unwrap_err();
+        assert_eq!(err, WsError::ConnectFailed { url });
+    }
+
+    #[tokio::test]
+    async fn client_sends_receives_and_closes() {
+        let url = echo_server().await;
+        let client = WsClient::connect(url.clone()).await.unwrap();
+        assert!(client.is_open());
+        assert_eq!(client.url(), url);
+        assert_eq!(client.recv().await.unwrap(), Message::from(DEFAULT_BANNER));
+
+        client.send_text("first").unwrap();
+        client.send_binary(&[4, 5]).unwrap();
+        assert_eq!(client.recv().await.unwrap(), Message::from("first"));
+        assert_eq!(client.recv().await.unwrap(), Message::Binary(vec![4, 5]));
+
+        assert!(matches!(
+            client.close(Some(1001), None),
+            Err(WsError::InvalidArgument(_))
+        ));
+        client.close(Some(1000), Some("done".to_string())).unwrap();
+        assert_eq!(
+            client.recv().await,
+            Err(WsError::Closed {
+                code: 1000,
+                reason: "done".to_string()
+            })
+        );
+        assert_eq!(client.ready_state(), WsStream::CLOSED);
+        assert!(client.send_text("late").is_err());
+    }
+
+    #[tokio::test]
+    async fn ping_frames_are_answered_not_returned() {
+        let url = format!(
+            "{}/?banner=&delay_ms=50&ping_interval_ms=5",
+            echo_server().await
+        );
+        let client = WsClient::connect(url).await.unwrap();
+        client.send_text("first").unwrap();
+        assert_eq!(client.recv().await.unwrap(), Message::from("first"));
+    }
+
+    #[tokio::test]
+    async fn closed_by_the_server() {
+        let url = echo_server().await;
+        let mut ws = WsStream::connect(&format!(
+            "{}/?close_after=1&close_code=4000&close_reason=bye",
+            url
+        ))
+        .await
+        .unwrap();
+        ws.send(Message::from("last")).await.unwrap();
+        assert_eq!(ws.next().await, Some(Ok(Message::from(DEFAULT_BANNER))));
+        assert_eq!(ws.next().await, Some(Ok(Message::from("last"))));
+        let closed = WsError::Closed {
+            code: 4000,
+            reason: "bye".to_string(),
+        };
+        assert_eq!(ws.next().await, Some(Err(closed)));
+        assert_eq!(ws.next().await, None);
+
+        // without a closing handshake
+        let mut ws = WsStream::connect(&format!("{}/?banner=&close_after=0&close_code=1006", url))
+            .await
+            .unwrap();
+        assert!(matches!(
+            ws.next().await,
+            Some(Err(WsError::Closed { code: 1006, .. }))
+        ));
+    }
+
+    fn record_events(client: &ReconnectingWsClient) -> Arc<Mutex<Vec<ReconnectEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        for set_hook in [
+            ReconnectingWsClient::set_onreconnecting,
+            ReconnectingWsClient::set_onreconnected,
+        ] {
+            let events = events.clone();
+            set_hook(
+                client,
+                Some(Box::new(move |event| events.lock().unwrap().push(event))),
+            );
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn reconnecting_client_reconnects_and_buffers() {
+        // each connection is dropped after one echo
+        let url = format!("{}/?close_after=1&close_code=1006", echo_server().await);
+        let options = ReconnectOptions {
+            initial_delay_ms: 10,
+            jitter: 0.0,
+            ..ReconnectOptions::default()
+        };
+        let client = ReconnectingWsClient::new(url, Some(options));
+        let events = record_events(&client);
+        client.send_text("buffered").unwrap();
+        assert_eq!(client.buffered_messages(), 1);
+        assert_eq!(client.recv().await.unwrap(), Message::from(DEFAULT_BANNER));
+        assert_eq!(client.recv().await.unwrap(), Message::from("buffered"));
+        assert_eq!(client.buffered_messages(), 0);
+
+        // sent while disconnected, "again" is delivered once reconnected
+        while client.is_connected() {
+            sleep(1).await;
+        }
+        client.send_text("again").unwrap();
+        assert_eq!(client.recv().await.unwrap(), Message::from(DEFAULT_BANNER));
+        assert_eq!(client.recv().await.unwrap(), Message::from("again"));
+        assert_eq!(
+            events.lock().unwrap()[..2],
+            [
+                ReconnectEvent::Reconnecting {
+                    attempt: 1,
+                    delay_ms: 10
+                },
+                ReconnectEvent::Reconnected { attempt: 1 },
+            ]
+        );
+
+        client.close(Some(1000), None).unwrap();
+        assert!(matches!(
+            client.recv().await,
+            Err(WsError::Closed { code: 1000, .. })
+        ));
+        assert!(client.send_text("late").is_err());
+    }
+
+    #[tokio::test]
+    async fn reconnecting_client_gives_up_after_max_attempts() {
+        let url = closed_port().await;
+        let options = ReconnectOptions {
+            initial_delay_ms: 10,
+            max_attempts: Some(2),
+            ..ReconnectOptions::default()
+        };
+        let client = ReconnectingWsClient::new(url.clone(), Some(options));
+        let events = record_events(&client);
+        assert_eq!(client.recv().await, Err(WsError::ConnectFailed { url }));
+        let attempts: Vec<u32> = (events.lock().unwrap().iter())
+            .map(|event| match event {
+                ReconnectEvent::Reconnecting { attempt, .. } => *attempt,
+                ReconnectEvent::Reconnected { .. } => panic!("{:?}", event),
+            })
+            .collect();
+        assert_eq!(attempts, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn reconnecting_client_backs_off_when_connections_drop_right_away() {
+        // each connection is closed as soon as it opens
+        let url = format!("{}/?banner=&close_after=0", echo_server().await);
+        let options = ReconnectOptions {
+            initial_delay_ms: 10,
+            jitter: 0.0,
+            max_attempts: Some(3),
+            ..ReconnectOptions::default()
+        };
+        let client = ReconnectingWsClient::new(url.clone(), Some(options));
+        let events = record_events(&client);
+        assert_eq!(client.recv().await, Err(WsError::ConnectFailed { url }));
+        let delays: Vec<(u32, u32)> = (events.lock().unwrap().iter())
+            .filter_map(|event| match event {
+                ReconnectEvent::Reconnecting { attempt, delay_ms } => Some((*attempt, *delay_ms)),
+                ReconnectEvent::Reconnected { .. } => None,
+            })
+            .collect();
+        assert_eq!(delays, [(1, 10), (2, 20), (3, 40)]);
+    }
+
+    #[test]
+    fn random_is_in_range() {
+        assert!((0..100).map(|_| random()).all(|r| (0.0..1.0).contains(&r)));
+    }
+}
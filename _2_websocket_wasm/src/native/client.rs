@@ -0,0 +1,66 @@
+use futures::lock::Mutex;
+use futures::StreamExt;
+
+use super::stream::{Sender, WsStream};
+use crate::{Message, WsError};
+
+/// A long-lived WebSocket connection
+///
+/// The connection is closed when the client is dropped
+pub struct WsClient {
+    url: String,
+    sender: Sender,
+    /// Read by one `recv` at a time, in the order they were called
+    stream: Mutex<WsStream>,
+}
+
+impl WsClient {
+    /// Open a connection to `endpoint`, returns once it's open. Must be called within a tokio runtime
+    pub async fn connect(endpoint: String) -> Result<WsClient, WsError> {
+        let stream = WsStream::connect(&endpoint).await?;
+        Ok(WsClient {
+            url: endpoint,
+            sender: stream.sender(),
+            stream: Mutex::new(stream),
+        })
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<(), WsError> {
+        self.sender.send(Message::from(text))
+    }
+
+    pub fn send_binary(&self, data: &[u8]) -> Result<(), WsError> {
+        self.sender.send(Message::from(data.to_vec()))
+    }
+
+    pub fn send(&self, msg: &Message) -> Result<(), WsError> {
+        self.sender.send(msg.clone())
+    }
+
+    /// The next message received, in order. Fails with `WsError::Closed` once the connection is closed and all messages are read
+    pub async fn recv(&self) -> Result<Message, WsError> {
+        let mut stream = self.stream.lock().await;
+        match stream.next().await {
+            Some(msg) => msg,
+            None => Err(stream.closed_error()),
+        }
+    }
+
+    /// Start the closing handshake, with an optional close code (1000 or 3000-4999) and reason
+    pub fn close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        self.sender.close(code, reason)
+    }
+
+    /// As `WebSocket.readyState`: 0 connecting, 1 open, 2 closing, 3 closed
+    pub fn ready_state(&self) -> u16 {
+        self.sender.ready_state()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.ready_state() == WsStream::OPEN
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+}
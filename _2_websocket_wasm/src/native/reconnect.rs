@@ -0,0 +1,103 @@
+use std::sync::{Arc, Mutex};
+
+use super::stream::WsStream;
+use crate::reconnect::{self, Handle};
+use crate::{Message, ReconnectEvent, ReconnectOptions, WsError};
+
+type Hook = Box<dyn FnMut(ReconnectEvent) + Send>;
+
+#[derive(Default)]
+struct Hooks {
+    onreconnecting: Mutex<Option<Hook>>,
+    onreconnected: Mutex<Option<Hook>>,
+}
+
+impl Hooks {
+    fn emit(&self, event: ReconnectEvent) {
+        let hook = match event {
+            ReconnectEvent::Reconnecting { .. } => &self.onreconnecting,
+            ReconnectEvent::Reconnected { .. } => &self.onreconnected,
+        };
+        if let Some(hook) = hook.lock().unwrap().as_mut() {
+            hook(event);
+        }
+    }
+}
+
+/// A WebSocket client that reconnects when the connection is lost, with exponential backoff and jitter
+///
+/// It connects in the background: messages sent while disconnected are buffered and sent in order once reconnected.
+/// Received messages are read with `recv` across reconnections
+pub struct ReconnectingWsClient {
+    handle: Handle,
+    hooks: Arc<Hooks>,
+}
+
+impl ReconnectingWsClient {
+    /// Must be called within a tokio runtime
+    pub fn new(endpoint: String, options: Option<ReconnectOptions>) -> Self {
+        let hooks = Arc::new(Hooks::default());
+        let (handle, driver) = reconnect::reconnecting(
+            endpoint,
+            options.unwrap_or_default(),
+            |url| async move { WsStream::connect(&url).await },
+            super::sleep,
+            super::random,
+            {
+                let hooks = hooks.clone();
+                move |event| hooks.emit(event)
+            },
+        );
+        tokio::spawn(driver);
+        ReconnectingWsClient { handle, hooks }
+    }
+
+    /// Sent now if connected, otherwise once reconnected
+    pub fn send_text(&self, text: &str) -> Result<(), WsError> {
+        self.handle.send(Message::from(text))
+    }
+
+    pub fn send_binary(&self, data: &[u8]) -> Result<(), WsError> {
+        self.handle.send(Message::from(data.to_vec()))
+    }
+
+    pub fn send(&self, msg: Message) -> Result<(), WsError> {
+        self.handle.send(msg)
+    }
+
+    /// The next message received, in order, whichever connection it came from.
+    /// Fails once the client is closed or gave up reconnecting, and all messages are read
+    pub async fn recv(&self) -> Result<Message, WsError> {
+        self.handle.recv().await
+    }
+
+    /// Stop reconnecting and close the connection, with an optional close code (1000 or 3000-4999) and reason
+    pub fn close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        self.handle.close(code, reason)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.handle.is_connected()
+    }
+
+    /// Messages waiting for the connection
+    pub fn buffered_messages(&self) -> usize {
+        self.handle.buffered_messages()
+    }
+
+    /// Called with `ReconnectEvent::Reconnecting` before waiting to reconnect
+    pub fn set_onreconnecting(&self, hook: Option<Box<dyn FnMut(ReconnectEvent) + Send>>) {
+        *self.hooks.onreconnecting.lock().unwrap() = hook;
+    }
+
+    /// Called with `ReconnectEvent::Reconnected` once reconnected
+    pub fn set_onreconnected(&self, hook: Option<Box<dyn FnMut(ReconnectEvent) + Send>>) {
+        *self.hooks.onreconnected.lock().unwrap() = hook;
+    }
+}
+
+impl Drop for ReconnectingWsClient {
+    fn drop(&mut self) {
+        let _ = self.close(None, None);
+    }
+}
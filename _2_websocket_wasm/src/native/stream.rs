@@ -0,0 +1,290 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc;
+use futures::future::{self, Either};
+use futures::task::AtomicWaker;
+use futures::{pin_mut, Sink, SinkExt, Stream, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{self, Message as Frame};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::protocol::{check_close, Connection};
+use crate::{Message, WsError};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+enum Command {
+    Send(Message),
+    Close(Option<CloseFrame<'static>>),
+}
+
+/// What the connection task shares with the stream
+struct State {
+    ready_state: AtomicU16,
+    /// Code and reason of the close frame
+    closed: Mutex<Option<(u16, String)>>,
+    /// Woken once closed
+    waker: AtomicWaker,
+}
+
+/// A connection as an async [`Stream`] of received messages and a [`Sink`] of messages to send
+///
+/// The stream yields the messages in order, then `Err(WsError::Closed)` with the close code and reason, then ends.
+/// The connection is run by a task: as in browsers, messages sent are queued, ping frames are answered,
+/// and dropping the stream closes the connection
+pub struct WsStream {
+    url: String,
+    sender: Sender,
+    events: mpsc::UnboundedReceiver<Result<Message, WsError>>,
+}
+
+impl WsStream {
+    /// The `readyState` of browsers
+    pub const CONNECTING: u16 = 0;
+    pub const OPEN: u16 = 1;
+    pub const CLOSING: u16 = 2;
+    pub const CLOSED: u16 = 3;
+
+    /// Open a connection to `endpoint`, returns once it's open. Must be called within a tokio runtime
+    pub async fn connect(endpoint: &str) -> Result<Self, WsError> {
+        console_log!("Connecting to {}", endpoint);
+        let (socket, _) =
+            tokio_tungstenite::connect_async(endpoint)
+                .await
+                .map_err(|err| match err {
+                    tungstenite::Error::Url(_) | tungstenite::Error::HttpFormat(_) => {
+                        WsError::InvalidUrl {
+                            url: endpoint.to_string(),
+                            message: err.to_string(),
+                        }
+                    }
+                    err => {
+                        console_err!("connection failed: {}", err);
+                        WsError::ConnectFailed {
+                            url: endpoint.to_string(),
+                        }
+                    }
+                })?;
+        console_log!("socket opened");
+
+        let (commands, commands_receiver) = mpsc::unbounded();
+        let (events_sender, events) = mpsc::unbounded();
+        let state = Arc::new(State {
+            ready_state: AtomicU16::new(Self::OPEN),
+            closed: Mutex::default(),
+            waker: AtomicWaker::new(),
+        });
+        tokio::spawn(run(socket, commands_receiver, events_sender, state.clone()));
+        Ok(WsStream {
+            url: endpoint.to_string(),
+            sender: Sender { commands, state },
+            events,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn ready_state(&self) -> u16 {
+        self.sender.ready_state()
+    }
+
+    pub(crate) fn sender(&self) -> Sender {
+        self.sender.clone()
+    }
+
+    /// `WsError::Closed` with the code and reason of the close frame, once there was one
+    pub(crate) fn closed_error(&self) -> WsError {
+        self.sender.closed_error()
+    }
+}
+
+/// Reads frames and carries out the commands, until the connection is closed
+async fn run(
+    mut socket: Socket,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    events: mpsc::UnboundedSender<Result<Message, WsError>>,
+    state: Arc<State>,
+) {
+    let mut closing = false;
+    loop {
+        let next = {
+            let command = async {
+                match closing {
+                    false => commands.next().await,
+                    true => future::pending().await,
+                }
+            };
+            pin_mut!(command);
+            match future::select(socket.next(), command).await {
+                Either::Left((frame, _)) => Either::Left(frame),
+                Either::Right((command, _)) => Either::Right(command),
+            }
+        };
+        match next {
+            Either::Left(Some(Ok(Frame::Text(text)))) => {
+                let _ = events.unbounded_send(Ok(Message::Text(text)));
+            }
+            Either::Left(Some(Ok(Frame::Binary(data)))) => {
+                let _ = events.unbounded_send(Ok(Message::Binary(data)));
+            }
+            // answered by tungstenite, which then ends the stream
+            Either::Left(Some(Ok(Frame::Close(frame)))) => {
+                let closed = match frame {
+                    Some(frame) => (frame.code.into(), frame.reason.into_owned()),
+                    None => (1005, String::new()),
+                };
+                console_log!("socket closed: {} {:?}", closed.0, closed.1);
+                state.closed.lock().unwrap().get_or_insert(closed);
+                state.ready_state.store(WsStream::CLOSING, Ordering::SeqCst);
+            }
+            // pings are answered by tungstenite
+            Either::Left(Some(Ok(_))) => {}
+            Either::Left(Some(Err(err))) => {
+                console_err!("connection error: {}", err);
+                break;
+            }
+            Either::Left(None) => break,
+            Either::Right(Some(Command::Send(msg))) => {
+                if let Err(err) = socket.send(Frame::from(msg)).await {
+                    console_err!("error sending message: {}", err);
+                }
+            }
+            // closed, or the stream was dropped
+            Either::Right(command) => {
+                closing = true;
+                state.ready_state.store(WsStream::CLOSING, Ordering::SeqCst);
+                let frame = match command {
+                    Some(Command::Close(frame)) => frame,
+                    _ => None,
+                };
+                if let Err(err) = socket.close(frame).await {
+                    console_err!("error closing: {}", err);
+                }
+            }
+        }
+    }
+    state.ready_state.store(WsStream::CLOSED, Ordering::SeqCst);
+    let (code, reason) = state
+        .closed
+        .lock()
+        .unwrap()
+        .get_or_insert((1006, String::new()))
+        .clone();
+    let _ = events.unbounded_send(Err(WsError::Closed { code, reason }));
+    state.waker.wake();
+}
+
+impl From<Message> for Frame {
+    fn from(msg: Message) -> Self {
+        match msg {
+            Message::Text(text) => Frame::Text(text),
+            Message::Binary(data) => Frame::Binary(data),
+        }
+    }
+}
+
+/// Sends and closes without borrowing the stream
+#[derive(Clone)]
+pub(crate) struct Sender {
+    commands: mpsc::UnboundedSender<Command>,
+    state: Arc<State>,
+}
+
+impl Sender {
+    pub(crate) fn ready_state(&self) -> u16 {
+        self.state.ready_state.load(Ordering::SeqCst)
+    }
+
+    /// Fails once the connection is closing
+    pub(crate) fn send(&self, msg: Message) -> Result<(), WsError> {
+        if self.ready_state() != WsStream::OPEN {
+            return Err(self.closed_error());
+        }
+        self.commands
+            .unbounded_send(Command::Send(msg))
+            .map_err(|_| self.closed_error())
+    }
+
+    /// Start the closing handshake, with an optional close code (1000 or 3000-4999) and reason
+    pub(crate) fn close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        check_close(code, reason.as_deref())?;
+        let ready_state = &self.state.ready_state;
+        let (open, closing) = (WsStream::OPEN, WsStream::CLOSING);
+        if ready_state
+            .compare_exchange(open, closing, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            let frame = code.map(|code| CloseFrame {
+                code: code.into(),
+                reason: reason.unwrap_or_default().into(),
+            });
+            let _ = self.commands.unbounded_send(Command::Close(frame));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn closed_error(&self) -> WsError {
+        match &*self.state.closed.lock().unwrap() {
+            Some((code, reason)) => WsError::Closed {
+                code: *code,
+                reason: reason.clone(),
+            },
+            // closing
+            None => WsError::Closed {
+                code: 1006,
+                reason: String::new(),
+            },
+        }
+    }
+}
+
+impl Stream for WsStream {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_next_unpin(cx)
+    }
+}
+
+impl Sink<Message> for WsStream {
+    type Error = WsError;
+
+    /// The connection is open once connected: nothing to wait for
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        match self.ready_state() {
+            WsStream::OPEN => Poll::Ready(Ok(())),
+            _ => Poll::Ready(Err(self.closed_error())),
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: Message) -> Result<(), WsError> {
+        self.sender.send(msg)
+    }
+
+    /// Messages are queued for the connection task: nothing to wait for
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Starts the closing handshake, and waits for the connection to be closed
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        self.sender.close(None, None)?;
+        self.sender.state.waker.register(cx.waker());
+        match self.ready_state() {
+            WsStream::CLOSED => Poll::Ready(Ok(())),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl Connection for WsStream {
+    fn start_close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        self.sender.close(code, reason)
+    }
+}
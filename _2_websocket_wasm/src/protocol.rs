@@ -0,0 +1,100 @@
+//! What both backends do the same way, over a [`Connection`] of either
+
+use std::future::Future;
+
+use futures::future::{self, Either};
+use futures::{pin_mut, Sink, SinkExt, Stream, StreamExt};
+
+use crate::{Message, WsError};
+
+/// A `WsStream` of either backend
+///
+/// Like browsers, they queue the messages sent: the sink doesn't need flushing
+pub(crate) trait Connection:
+    Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin
+{
+    /// Start the closing handshake, with an optional close code (1000 or 3000-4999) and reason
+    fn start_close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError>;
+}
+
+/// Sends `message`, then resolves with the first message received
+pub(crate) async fn exchange<C: Connection>(
+    ws: &mut C,
+    message: Message,
+) -> Result<Message, WsError> {
+    ws.send(message).await?;
+    console_log!("message successfully sent");
+    match ws.next().await {
+        Some(reply) => reply,
+        None => Err(WsError::Closed {
+            code: 1006,
+            reason: String::new(),
+        }),
+    }
+}
+
+/// `fut`, unless it's still pending after `timeout_ms` (`WsError::Timeout`) or once `aborted` completes (`WsError::Aborted`).
+/// `fut` is dropped either way
+pub(crate) async fn cancellable<T, S>(
+    fut: impl Future<Output = Result<T, WsError>>,
+    timeout_ms: Option<u32>,
+    sleep: impl FnOnce(u32) -> S,
+    aborted: impl Future<Output = ()>,
+) -> Result<T, WsError>
+where
+    S: Future<Output = ()>,
+{
+    let timeout = async {
+        match timeout_ms {
+            Some(ms) => sleep(ms).await,
+            None => future::pending().await,
+        }
+    };
+    pin_mut!(fut, timeout, aborted);
+
+    match future::select(fut, future::select(timeout, aborted)).await {
+        Either::Left((output, _)) => output,
+        Either::Right((Either::Left(_), _)) => {
+            let ms = timeout_ms.unwrap_or_default();
+            console_err!("no reply after {} ms", ms);
+            Err(WsError::Timeout { ms })
+        }
+        Either::Right((Either::Right(_), _)) => {
+            console_err!("aborted");
+            Err(WsError::Aborted)
+        }
+    }
+}
+
+/// The close codes and reasons browsers accept
+pub(crate) fn check_close(code: Option<u16>, reason: Option<&str>) -> Result<(), WsError> {
+    if code.is_some_and(|code| code != 1000 && !(3000..=4999).contains(&code)) {
+        let msg = "close code must be 1000 or between 3000 and 4999";
+        return Err(WsError::InvalidArgument(msg.to_string()));
+    }
+    if reason.is_some_and(|reason| reason.len() > 123) {
+        let msg = "close reason must be at most 123 bytes";
+        return Err(WsError::InvalidArgument(msg.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_codes() {
+        assert!(check_close(None, None).is_ok());
+        assert!(check_close(Some(1000), Some("done")).is_ok());
+        assert!(check_close(Some(4999), None).is_ok());
+        for code in [999, 1001, 1006, 2999, 5000] {
+            assert!(matches!(
+                check_close(Some(code), None),
+                Err(WsError::InvalidArgument(_))
+            ));
+        }
+        let reason = "x".repeat(124);
+        assert!(check_close(Some(1000), Some(&reason)).is_err());
+    }
+}
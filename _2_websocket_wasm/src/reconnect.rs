@@ -0,0 +1,405 @@
+//! Reconnection, for the `ReconnectingWsClient` of both backends
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc;
+use futures::future::{self, Either};
+use futures::{pin_mut, SinkExt, StreamExt};
+use wasm_bindgen::prelude::*;
+
+use crate::protocol::{check_close, Connection};
+use crate::{Message, WsError};
+
+/// Backoff between reconnection attempts
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectOptions {
+    /// Delay before the first attempt
+    pub initial_delay_ms: u32,
+    pub max_delay_ms: u32,
+    /// Each attempt waits `factor` times longer than the previous one, up to `max_delay_ms`
+    pub factor: f64,
+    /// Fraction of the delay drawn at random, between 0 (exact delays) and 1 (anywhere between 0 and the delay),
+    /// so that clients disconnected together don't reconnect together
+    pub jitter: f64,
+    /// Consecutive failed attempts before giving up, `undefined` to retry forever
+    pub max_attempts: Option<u32>,
+    /// How long a connection must stay open for the backoff to start over.
+    /// Connections lost earlier count as failed attempts, so that a server closing them right away isn't retried at full speed forever
+    pub stable_after_ms: u32,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        ReconnectOptions {
+            initial_delay_ms: 500,
+            max_delay_ms: 30_000,
+            factor: 2.0,
+            jitter: 0.5,
+            max_attempts: None,
+            stable_after_ms: 5_000,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ReconnectOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay before reconnection attempt `attempt` (from 1), with `random` uniform in [0, 1)
+    pub fn delay_ms(&self, attempt: u32, random: f64) -> u32 {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = (self.initial_delay_ms as f64 * self.factor.max(1.0).powi(exponent))
+            .min(self.max_delay_ms as f64);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random.clamp(0.0, 1.0);
+        (delay * (1.0 - jitter)).round() as u32
+    }
+}
+
+/// What the `onreconnecting` and `onreconnected` hooks are called with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectEvent {
+    /// Waiting `delay_ms` before attempt `attempt` (from 1)
+    Reconnecting {
+        attempt: u32,
+        delay_ms: u32,
+    },
+    Reconnected {
+        attempt: u32,
+    },
+}
+
+pub(crate) enum Command {
+    Send(Message),
+    Close {
+        code: Option<u16>,
+        reason: Option<String>,
+    },
+}
+
+/// What the client reads of the task keeping it connected
+#[derive(Default)]
+struct Status {
+    connected: AtomicBool,
+    /// Messages sent, not yet handed to a connection
+    buffered: AtomicUsize,
+    /// Why the client stopped: `recv` fails with it once the messages are all read
+    ended: Mutex<Option<WsError>>,
+}
+
+impl Status {
+    fn ended(&self) -> Option<WsError> {
+        self.ended.lock().unwrap().clone()
+    }
+
+    fn end(&self, err: WsError) {
+        self.ended.lock().unwrap().get_or_insert(err);
+    }
+}
+
+/// The client side of a reconnecting connection
+pub(crate) struct Handle {
+    commands: mpsc::UnboundedSender<Command>,
+    inbox: futures::lock::Mutex<mpsc::UnboundedReceiver<Result<Message, WsError>>>,
+    status: Arc<Status>,
+}
+
+impl Handle {
+    /// Sent now if connected, otherwise once reconnected
+    pub(crate) fn send(&self, msg: Message) -> Result<(), WsError> {
+        if let Some(err) = self.status.ended() {
+            return Err(err);
+        }
+        self.status.buffered.fetch_add(1, Ordering::SeqCst);
+        if self.commands.unbounded_send(Command::Send(msg)).is_err() {
+            self.status.buffered.fetch_sub(1, Ordering::SeqCst);
+            return Err(self.status.ended().unwrap_or(WsError::Aborted));
+        }
+        Ok(())
+    }
+
+    /// The next message received, whichever connection it came from
+    pub(crate) async fn recv(&self) -> Result<Message, WsError> {
+        match self.inbox.lock().await.next().await {
+            Some(msg) => msg,
+            None => Err(self.status.ended().unwrap_or(WsError::Closed {
+                code: 1005,
+                reason: String::new(),
+            })),
+        }
+    }
+
+    /// Stop reconnecting and close the connection
+    pub(crate) fn close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        check_close(code, reason.as_deref())?;
+        self.status.end(WsError::Closed {
+            code: code.unwrap_or(1005),
+            reason: reason.clone().unwrap_or_default(),
+        });
+        let _ = self
+            .commands
+            .unbounded_send(Command::Close { code, reason });
+        Ok(())
+    }
+
+    pub(crate) fn is_connected(&self) -> bool {
+        self.status.connected.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn buffered_messages(&self) -> usize {
+        self.status.buffered.load(Ordering::SeqCst)
+    }
+}
+
+/// A reconnecting client: its handle, and the task keeping it connected, for the backend to spawn.
+/// The task stops once the handle is dropped
+pub(crate) fn reconnecting<C, F, S>(
+    endpoint: String,
+    options: ReconnectOptions,
+    connect: impl Fn(String) -> F,
+    sleep: impl Fn(u32) -> S,
+    random: fn() -> f64,
+    on_event: impl FnMut(ReconnectEvent),
+) -> (Handle, impl Future<Output = ()>)
+where
+    C: Connection,
+    F: Future<Output = Result<C, WsError>>,
+    S: Future<Output = ()>,
+{
+    let (commands_sender, commands) = mpsc::unbounded();
+    let (inbox_sender, inbox) = mpsc::unbounded();
+    let status = Arc::new(Status::default());
+    let handle = Handle {
+        commands: commands_sender,
+        inbox: futures::lock::Mutex::new(inbox),
+        status: status.clone(),
+    };
+    let driver = Driver {
+        endpoint,
+        options,
+        connect,
+        sleep,
+        random,
+        on_event,
+        commands,
+        inbox: inbox_sender,
+        status,
+        outbox: VecDeque::new(),
+    };
+    (handle, driver.run())
+}
+
+struct Driver<Co, Sl, E> {
+    endpoint: String,
+    options: ReconnectOptions,
+    connect: Co,
+    sleep: Sl,
+    random: fn() -> f64,
+    on_event: E,
+    commands: mpsc::UnboundedReceiver<Command>,
+    inbox: mpsc::UnboundedSender<Result<Message, WsError>>,
+    status: Arc<Status>,
+    /// Messages sent while disconnected, flushed in order once reconnected
+    outbox: VecDeque<Message>,
+}
+
+/// Whether a lost connection stayed open for `stable_after_ms`
+enum Stability {
+    Stable,
+    Unstable,
+}
+
+/// The client closed, or was dropped
+struct Stop {
+    code: Option<u16>,
+    reason: Option<String>,
+}
+
+impl<C, F, S, Co, Sl, E> Driver<Co, Sl, E>
+where
+    C: Connection,
+    F: Future<Output = Result<C, WsError>>,
+    S: Future<Output = ()>,
+    Co: Fn(String) -> F,
+    Sl: Fn(u32) -> S,
+    E: FnMut(ReconnectEvent),
+{
+    /// Keep connecting until stopped, or until `max_attempts` consecutive attempts failed
+    async fn run(mut self) {
+        let mut attempt = 0;
+        loop {
+            if attempt > 0 {
+                if self.options.max_attempts.is_some_and(|max| attempt > max) {
+                    let url = self.endpoint.clone();
+                    self.status.end(WsError::ConnectFailed { url });
+                    return;
+                }
+                let delay_ms = self.options.delay_ms(attempt, (self.random)());
+                console_log!("reconnecting in {} ms (attempt {})", delay_ms, attempt);
+                (self.on_event)(ReconnectEvent::Reconnecting { attempt, delay_ms });
+                if self.buffering((self.sleep)(delay_ms)).await.is_err() {
+                    return;
+                }
+            }
+
+            let mut conn = match self.buffering((self.connect)(self.endpoint.clone())).await {
+                Ok(Ok(conn)) => conn,
+                // retrying won't fix the URL
+                Ok(Err(err @ WsError::InvalidUrl { .. })) => {
+                    self.status.end(err);
+                    return;
+                }
+                Ok(Err(err)) => {
+                    console_err!("connection failed: {}", err);
+                    attempt += 1;
+                    continue;
+                }
+                Err(_) => return,
+            };
+            self.status.connected.store(true, Ordering::SeqCst);
+            if attempt > 0 {
+                (self.on_event)(ReconnectEvent::Reconnected { attempt });
+            }
+            let stopped = self.forward(&mut conn).await;
+            self.status.connected.store(false, Ordering::SeqCst);
+            match stopped {
+                Ok(Stability::Stable) => attempt = 1,
+                Ok(Stability::Unstable) => attempt += 1,
+                Err(Stop { code, reason }) => {
+                    let _ = conn.start_close(code, reason);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Runs `fut`, buffering the messages sent meanwhile
+    async fn buffering<T>(&mut self, fut: impl Future<Output = T>) -> Result<T, Stop> {
+        pin_mut!(fut);
+        loop {
+            let command = match future::select(fut.as_mut(), self.commands.next()).await {
+                Either::Left((output, _)) => return Ok(output),
+                Either::Right((command, _)) => command,
+            };
+            match command {
+                Some(Command::Send(msg)) => self.outbox.push_back(msg),
+                Some(Command::Close { code, reason }) => return Err(Stop { code, reason }),
+                None => {
+                    return Err(Stop {
+                        code: None,
+                        reason: None,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Forward the messages both ways, until the connection is lost (`Ok`)
+    async fn forward(&mut self, conn: &mut C) -> Result<Stability, Stop> {
+        let stable = (self.sleep)(self.options.stable_after_ms);
+        pin_mut!(stable);
+        let mut stability = Stability::Unstable;
+        while let Some(msg) = self.outbox.pop_front() {
+            if let Err(msg) = self.send(conn, msg).await {
+                self.outbox.push_front(msg);
+                return Ok(stability);
+            }
+        }
+        loop {
+            let events = future::select(conn.next(), self.commands.next());
+            let next = match stability {
+                Stability::Stable => events.await,
+                Stability::Unstable => match future::select(events, stable.as_mut()).await {
+                    Either::Left((next, _)) => next,
+                    Either::Right(_) => {
+                        stability = Stability::Stable;
+                        continue;
+                    }
+                },
+            };
+            let next = match next {
+                Either::Left((msg, _)) => Either::Left(msg),
+                Either::Right((command, _)) => Either::Right(command),
+            };
+            match next {
+                Either::Left(Some(Err(WsError::Closed { code, reason }))) => {
+                    console_err!("connection lost: {} {}", code, reason);
+                    return Ok(stability);
+                }
+                Either::Left(None) => return Ok(stability),
+                Either::Left(Some(msg)) => {
+                    let _ = self.inbox.unbounded_send(msg);
+                }
+                Either::Right(Some(Command::Send(msg))) => {
+                    if let Err(msg) = self.send(conn, msg).await {
+                        self.outbox.push_front(msg);
+                        return Ok(stability);
+                    }
+                }
+                Either::Right(Some(Command::Close { code, reason })) => {
+                    return Err(Stop { code, reason })
+                }
+                Either::Right(None) => {
+                    return Err(Stop {
+                        code: None,
+                        reason: None,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Hands `msg` to the connection, or gives it back if the connection is lost
+    async fn send(&mut self, conn: &mut C, msg: Message) -> Result<(), Message> {
+        if future::poll_fn(|cx| conn.poll_ready_unpin(cx))
+            .await
+            .is_err()
+        {
+            return Err(msg);
+        }
+        self.status.buffered.fetch_sub(1, Ordering::SeqCst);
+        if let Err(err) = conn.start_send_unpin(msg) {
+            console_err!("error sending message: {}", err);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        let options = ReconnectOptions {
+            jitter: 0.0,
+            ..ReconnectOptions::default()
+        };
+        let delays: Vec<u32> = (1..=8)
+            .map(|attempt| options.delay_ms(attempt, 0.7))
+            .collect();
+        assert_eq!(delays, [500, 1000, 2000, 4000, 8000, 16000, 30000, 30000]);
+        assert_eq!(options.delay_ms(u32::MAX, 0.0), 30000);
+    }
+
+    #[test]
+    fn jitter_shortens_the_delay() {
+        let options = ReconnectOptions::default();
+        assert_eq!(options.delay_ms(3, 0.0), 2000);
+        assert_eq!(options.delay_ms(3, 0.5), 1500);
+        assert_eq!(options.delay_ms(3, 0.999), 1001);
+
+        let full = ReconnectOptions {
+            jitter: 1.0,
+            ..options
+        };
+        assert_eq!(full.delay_ms(3, 0.5), 1000);
+        assert!((0..100).all(|i| full.delay_ms(3, i as f64 / 100.0) <= 2000));
+    }
+}
@@ -0,0 +1,154 @@
+//! Request/response matching, for the `ws_request` of both backends
+
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+
+use crate::protocol::Connection;
+use crate::{Message, WsError};
+
+/// Which message answers a request. The messages received before it, e.g. a greeting banner, are skipped
+///
+/// `P` is the predicate type of the backend: a closure natively, a JS function in browsers
+pub enum Matcher<P> {
+    /// The message sent, echoed back
+    Echo,
+    /// A JSON reply whose field `0` equals the one of the request, e.g. `"id"` for JSON-RPC
+    JsonId(String),
+    /// The first message accepted by the predicate
+    Predicate(P),
+}
+
+/// What a `Matcher::Predicate` is called with
+pub(crate) trait Predicate {
+    fn accepts(&mut self, reply: &Message) -> Result<bool, WsError>;
+}
+
+impl<F: FnMut(&Message) -> bool> Predicate for F {
+    fn accepts(&mut self, reply: &Message) -> Result<bool, WsError> {
+        Ok(self(reply))
+    }
+}
+
+/// A matcher, bound to the request it matches replies of
+enum Expected<P> {
+    Echo(Message),
+    JsonId { field: String, id: Value },
+    Predicate(P),
+}
+
+impl<P: Predicate> Expected<P> {
+    fn new(matcher: Matcher<P>, request: &Message) -> Result<Self, WsError> {
+        Ok(match matcher {
+            Matcher::Echo => Expected::Echo(request.clone()),
+            Matcher::JsonId(field) => {
+                let Some(id) = json_field(request, &field) else {
+                    let msg = format!("request must be a JSON object with a {:?} field", field);
+                    return Err(WsError::InvalidArgument(msg));
+                };
+                Expected::JsonId { field, id }
+            }
+            Matcher::Predicate(predicate) => Expected::Predicate(predicate),
+        })
+    }
+
+    fn matches(&mut self, reply: &Message) -> Result<bool, WsError> {
+        match self {
+            Expected::Echo(request) => Ok(reply == request),
+            Expected::JsonId { field, id } => Ok(json_field(reply, field).as_ref() == Some(id)),
+            Expected::Predicate(predicate) => predicate.accepts(reply),
+        }
+    }
+}
+
+/// Field `field` of a text message holding a JSON object
+fn json_field(msg: &Message, field: &str) -> Option<Value> {
+    let Message::Text(text) = msg else {
+        return None;
+    };
+    let mut value: Value = serde_json::from_str(text).ok()?;
+    value.get_mut(field).map(Value::take)
+}
+
+/// Sends `message`, then resolves with the first message received that `matcher` accepts
+pub(crate) async fn request<C: Connection, P: Predicate>(
+    ws: &mut C,
+    message: Message,
+    matcher: Matcher<P>,
+) -> Result<Message, WsError> {
+    let mut expected = Expected::new(matcher, &message)?;
+    ws.send(message).await?;
+    loop {
+        let reply = match ws.next().await {
+            Some(reply) => reply?,
+            None => {
+                return Err(WsError::Closed {
+                    code: 1006,
+                    reason: String::new(),
+                })
+            }
+        };
+        if expected.matches(&reply)? {
+            return Ok(reply);
+        }
+        console_log!("skipped a message not answering the request");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Matcher = super::Matcher<fn(&Message) -> bool>;
+
+    fn expect(matcher: Matcher, request: &str) -> Expected<fn(&Message) -> bool> {
+        Expected::new(matcher, &Message::from(request)).unwrap()
+    }
+
+    #[test]
+    fn echo_matches_the_request_only() {
+        let mut expected = expect(Matcher::Echo, "hello");
+        assert!(!expected.matches(&Message::from("Welcome")).unwrap());
+        assert!(!expected
+            .matches(&Message::Binary(b"hello".to_vec()))
+            .unwrap());
+        assert!(expected.matches(&Message::from("hello")).unwrap());
+    }
+
+    #[test]
+    fn json_id_correlates_replies() {
+        let request = r#"{"id": 7, "method": "ping"}"#;
+        let mut expected = expect(Matcher::JsonId("id".to_string()), request);
+        for unrelated in [
+            "not json",
+            r#"{"method": "notify"}"#,
+            r#"{"id": 6, "result": "pong"}"#,
+            r#"{"id": "7", "result": "pong"}"#,
+            "[7]",
+        ] {
+            assert!(
+                !expected.matches(&Message::from(unrelated)).unwrap(),
+                "{}",
+                unrelated
+            );
+        }
+        assert!(!expected.matches(&Message::Binary(vec![7])).unwrap());
+        let reply = Message::from(r#"{"result": "pong", "id": 7}"#);
+        assert!(expected.matches(&reply).unwrap());
+
+        for request in ["not json", r#"{"method": "ping"}"#] {
+            let matcher = Matcher::JsonId("id".to_string());
+            assert!(matches!(
+                Expected::new(matcher, &Message::from(request)),
+                Err(WsError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn predicates_pick_the_reply() {
+        let predicate: fn(&Message) -> bool = |reply| matches!(reply, Message::Binary(_));
+        let mut expected = expect(Matcher::Predicate(predicate), "hello");
+        assert!(!expected.matches(&Message::from("hello")).unwrap());
+        assert!(expected.matches(&Message::Binary(vec![1])).unwrap());
+    }
+}
@@ -0,0 +1,112 @@
+//! The browser backend, over `web_sys::WebSocket`
+
+use std::future::Future;
+
+use futures::future;
+use js_sys::Promise;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+use web_sys::AbortSignal;
+
+use crate::request::Matcher;
+use crate::{protocol, Message, WsError};
+
+mod cancel;
+mod client;
+mod error;
+mod message;
+mod reconnect;
+mod request;
+mod stream;
+pub use client::WsClient;
+pub use error::ws_error_classes;
+pub use reconnect::ReconnectingWsClient;
+pub use request::JsMatcher;
+pub use stream::WsStream;
+
+/// Rejects with a `TimeoutError` after `timeout_ms`, an `AbortError` once `signal` is aborted,
+/// or another of the [`WsError`] classes, see [`ws_error_classes`]
+#[wasm_bindgen]
+// exporting async functions to wasm needs crate wasm_bindgen_futures
+pub async fn ws_ping(
+    endpoint: &str,
+    message: &str,
+    timeout_ms: Option<u32>,
+    signal: Option<AbortSignal>,
+) -> Promise {
+    let message = Message::from(message);
+    future_to_promise(ping(endpoint.to_string(), message, timeout_ms, signal))
+}
+
+/// Same as `ws_ping`, with a binary message
+#[wasm_bindgen]
+pub async fn ws_ping_binary(
+    endpoint: &str,
+    message: &[u8],
+    timeout_ms: Option<u32>,
+    signal: Option<AbortSignal>,
+) -> Promise {
+    let message = Message::from(message.to_vec());
+    future_to_promise(ping(endpoint.to_string(), message, timeout_ms, signal))
+}
+
+/// Sends `message` (a string, `Uint8Array` or `ArrayBuffer`), then resolves with the first reply `matcher` accepts,
+/// skipping the others, e.g. a greeting banner. It rejects like `ws_ping`, and with an `InvalidArgumentError`
+/// when the matcher is invalid or throws
+#[wasm_bindgen]
+pub async fn ws_request(
+    endpoint: &str,
+    message: JsValue,
+    matcher: JsMatcher,
+    timeout_ms: Option<u32>,
+    signal: Option<AbortSignal>,
+) -> Promise {
+    let endpoint = endpoint.to_string();
+    future_to_promise(async move {
+        let message = Message::from_js(&message)?;
+        let matcher = Matcher::from_js(&matcher)?;
+        let exchange = async {
+            let mut ws = WsStream::connect(&endpoint).await?;
+            crate::request::request(&mut ws, message, matcher).await
+        };
+        cancelled_by(exchange, timeout_ms, signal).await
+    })
+}
+
+/// Resolves with the first message received: a string for text, a `Uint8Array` for binary.
+///
+/// The socket, timer and abort listener own their callbacks: whatever the outcome,
+/// they are dropped when this returns, which closes the socket and detaches everything
+async fn ping(
+    endpoint: String,
+    message: Message,
+    timeout_ms: Option<u32>,
+    signal: Option<AbortSignal>,
+) -> Result<JsValue, JsValue> {
+    let exchange = async {
+        let mut ws = WsStream::connect(&endpoint).await?;
+        protocol::exchange(&mut ws, message).await
+    };
+    cancelled_by(exchange, timeout_ms, signal).await
+}
+
+/// `exchange` as a JS value, unless `signal` is aborted or `timeout_ms` elapsed first
+async fn cancelled_by(
+    exchange: impl Future<Output = Result<Message, WsError>>,
+    timeout_ms: Option<u32>,
+    signal: Option<AbortSignal>,
+) -> Result<JsValue, JsValue> {
+    if signal.as_ref().is_some_and(|signal| signal.aborted()) {
+        return Err(WsError::Aborted.into());
+    }
+    let aborted = async {
+        match &signal {
+            Some(signal) => cancel::aborted(signal).await,
+            None => future::pending().await,
+        }
+    };
+    protocol::cancellable(exchange, timeout_ms, cancel::sleep, aborted)
+        .await
+        .map(JsValue::from)
+        .map_err(JsValue::from)
+}
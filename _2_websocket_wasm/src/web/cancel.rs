@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use futures::channel::oneshot;
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+use web_sys::AbortSignal;
+
+#[wasm_bindgen]
+extern "C" {
+    // globals of both browsers and node, returning a number or a `Timeout` object
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &Function, timeout: i32) -> JsValue;
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(id: &JsValue);
+}
+
+/// A callback that completes `receiver` the first time it's called
+fn notifier() -> (Closure<dyn FnMut()>, oneshot::Receiver<()>) {
+    let (sender, receiver) = oneshot::channel();
+    let sender = Rc::new(RefCell::new(Some(sender)));
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        if let Some(sender) = sender.borrow_mut().take() {
+            let _ = sender.send(());
+        }
+    });
+    (callback, receiver)
+}
+
+/// Completes after `ms` milliseconds. Dropping it clears the timer and frees its callback
+pub(crate) struct Sleep {
+    id: JsValue,
+    receiver: oneshot::Receiver<()>,
+    _callback: Closure<dyn FnMut()>,
+}
+
+pub(crate) fn sleep(ms: u32) -> Sleep {
+    let (callback, receiver) = notifier();
+    let id = set_timeout(
+        callback.as_ref().unchecked_ref(),
+        ms.min(i32::MAX as u32) as i32,
+    );
+    Sleep {
+        id,
+        receiver,
+        _callback: callback,
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.receiver).poll(cx).map(|_| ())
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        clear_timeout(&self.id);
+    }
+}
+
+/// Completes once `signal` is aborted, right away if it already is. Dropping it removes its listener
+pub(crate) struct Aborted {
+    signal: AbortSignal,
+    receiver: oneshot::Receiver<()>,
+    callback: Closure<dyn FnMut()>,
+}
+
+pub(crate) fn aborted(signal: &AbortSignal) -> Aborted {
+    let (callback, receiver) = notifier();
+    if signal.aborted() {
+        let _ = callback
+            .as_ref()
+            .unchecked_ref::<Function>()
+            .call0(&JsValue::NULL);
+    } else {
+        let _ = signal.add_event_listener_with_callback("abort", callback.as_ref().unchecked_ref());
+    }
+    Aborted {
+        signal: signal.clone(),
+        receiver,
+        callback,
+    }
+}
+
+impl Future for Aborted {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.receiver).poll(cx).map(|_| ())
+    }
+}
+
+impl Drop for Aborted {
+    fn drop(&mut self) {
+        let _ = self
+            .signal
+            .remove_event_listener_with_callback("abort", self.callback.as_ref().unchecked_ref());
+    }
+}
@@ -0,0 +1,141 @@
+use std::rc::Rc;
+
+use futures::lock::Mutex;
+use futures::StreamExt;
+use js_sys::Promise;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+use web_sys::{BinaryType, WebSocket};
+
+use super::stream::{self, CloseWatcher, WsStream};
+use crate::{Message, WsError};
+
+/// A long-lived WebSocket connection
+///
+/// The socket is closed when the client is dropped (`free()` in JS)
+#[wasm_bindgen]
+pub struct WsClient {
+    ws: WebSocket,
+    closed: CloseWatcher,
+    /// Read by one `recv` at a time, in the order they were called
+    stream: Rc<Mutex<WsStream>>,
+}
+
+#[wasm_bindgen]
+impl WsClient {
+    /// Open a connection to `endpoint`, resolves once the socket is open
+    pub async fn connect(endpoint: String) -> Result<WsClient, WsError> {
+        let stream = WsStream::connect(&endpoint).await?;
+        Ok(WsClient {
+            ws: stream.websocket().clone(),
+            closed: stream.close_watcher(),
+            stream: Rc::new(Mutex::new(stream)),
+        })
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<(), WsError> {
+        self.check_open()?;
+        self.ws.send_with_str(text).map_err(WsError::from_js)
+    }
+
+    pub fn send_binary(&self, data: &[u8]) -> Result<(), WsError> {
+        self.check_open()?;
+        self.ws.send_with_u8_array(data).map_err(WsError::from_js)
+    }
+
+    /// Resolves with the next message received, in order: a string for text, a `Uint8Array` for binary.
+    /// Rejects once the connection is closed and all messages are read
+    pub fn next_message(&self) -> Promise {
+        let stream = self.stream.clone();
+        future_to_promise(async move {
+            Self::recv_from(&stream)
+                .await
+                .map(JsValue::from)
+                .map_err(JsValue::from)
+        })
+    }
+
+    /// Start the closing handshake, with an optional close code (1000 or 3000-4999) and reason
+    pub fn close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        stream::close(&self.ws, code, reason)
+    }
+
+    /// `WebSocket.readyState`: 0 connecting, 1 open, 2 closing, 3 closed
+    #[wasm_bindgen(getter)]
+    pub fn ready_state(&self) -> u16 {
+        self.ws.ready_state()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_open(&self) -> bool {
+        self.ws.ready_state() == WebSocket::OPEN
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn url(&self) -> String {
+        self.ws.url()
+    }
+
+    /// How binary messages are delivered by the browser, "arraybuffer" (default) or "blob".
+    /// Either way, they are returned as a `Uint8Array`
+    #[wasm_bindgen(getter)]
+    pub fn binary_type(&self) -> String {
+        match self.ws.binary_type() {
+            BinaryType::Blob => "blob".to_string(),
+            _ => "arraybuffer".to_string(),
+        }
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_binary_type(&self, binary_type: &str) -> Result<(), WsError> {
+        let binary_type = match binary_type {
+            "arraybuffer" => BinaryType::Arraybuffer,
+            "blob" => BinaryType::Blob,
+            _ => {
+                let msg = "binary_type must be \"arraybuffer\" or \"blob\"";
+                return Err(WsError::InvalidArgument(msg.to_string()));
+            }
+        };
+        self.ws.set_binary_type(binary_type);
+        Ok(())
+    }
+}
+
+impl WsClient {
+    pub fn send(&self, msg: &Message) -> Result<(), WsError> {
+        self.check_open()?;
+        msg.send(&self.ws).map_err(WsError::from_js)
+    }
+
+    /// Browsers silently drop the messages sent once the socket is closing
+    fn check_open(&self) -> Result<(), WsError> {
+        match self.ws.ready_state() {
+            WebSocket::CLOSING | WebSocket::CLOSED => Err(self.closed.error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The next message received, in order. Fails with `WsError::Closed` once the connection is closed and all messages are read
+    pub async fn recv(&self) -> Result<Message, WsError> {
+        Self::recv_from(&self.stream).await
+    }
+
+    async fn recv_from(stream: &Mutex<WsStream>) -> Result<Message, WsError> {
+        let mut stream = stream.lock().await;
+        match stream.next().await {
+            Some(msg) => msg,
+            None => Err(stream.closed_error()),
+        }
+    }
+}
+
+impl Drop for WsClient {
+    /// Pending `recv` calls still hold the stream: they complete with the close event
+    fn drop(&mut self) {
+        if self.ws.ready_state() == WebSocket::CONNECTING
+            || self.ws.ready_state() == WebSocket::OPEN
+        {
+            let _ = self.ws.close();
+        }
+    }
+}
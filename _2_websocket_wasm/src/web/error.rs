@@ -0,0 +1,68 @@
+use wasm_bindgen::prelude::*;
+
+use crate::WsError;
+
+#[wasm_bindgen(module = "/js/errors.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = createError)]
+    fn create_error(
+        class_name: &str,
+        message: &str,
+        code: &str,
+        close_code: Option<u16>,
+        reason: Option<String>,
+    ) -> JsValue;
+    #[wasm_bindgen(js_name = errorClasses)]
+    fn error_classes() -> js_sys::Object;
+}
+
+impl WsError {
+    /// The name of the JS error class
+    fn class_name(&self) -> &'static str {
+        match self {
+            WsError::InvalidUrl { .. } => "InvalidUrlError",
+            WsError::ConnectFailed { .. } => "ConnectError",
+            WsError::Closed { .. } => "ClosedError",
+            WsError::Timeout { .. } => "TimeoutError",
+            WsError::Aborted => "AbortError",
+            WsError::UnexpectedMessageType => "UnexpectedMessageTypeError",
+            WsError::InvalidArgument(_) => "InvalidArgumentError",
+            WsError::Js(_) => "WsError",
+        }
+    }
+
+    pub(crate) fn from_js(err: JsValue) -> Self {
+        WsError::Js(js_message(err))
+    }
+}
+
+/// The message of a JS exception
+pub(crate) fn js_message(err: JsValue) -> String {
+    match err.dyn_into::<js_sys::Error>() {
+        Ok(err) => err.message().into(),
+        Err(err) => format!("{:?}", err),
+    }
+}
+
+impl From<WsError> for JsValue {
+    fn from(err: WsError) -> Self {
+        let (close_code, reason) = match &err {
+            WsError::Closed { code, reason } => (Some(*code), Some(reason.clone())),
+            _ => (None, None),
+        };
+        create_error(
+            err.class_name(),
+            &err.to_string(),
+            err.code(),
+            close_code,
+            reason,
+        )
+    }
+}
+
+/// The error classes, e.g. `err instanceof ws_error_classes().TimeoutError`.
+/// All of them extend `WsError`, itself an `Error`
+#[wasm_bindgen]
+pub fn ws_error_classes() -> js_sys::Object {
+    error_classes()
+}
@@ -0,0 +1,60 @@
+use js_sys::{ArrayBuffer, JsString, Uint8Array};
+use wasm_bindgen::prelude::*;
+use web_sys::{Blob, WebSocket};
+
+use crate::{Message, WsError};
+
+/// What a `MessageEvent` carries: a message, or a `Blob` still to be read (with `binaryType = "blob"`)
+pub(crate) enum EventData {
+    Message(Message),
+    Blob(Blob),
+}
+
+impl Message {
+    pub(crate) fn from_event_data(data: JsValue) -> Result<EventData, WsError> {
+        if let Some(text) = data.as_string() {
+            Ok(EventData::Message(Message::Text(text)))
+        } else if let Some(buffer) = data.dyn_ref::<ArrayBuffer>() {
+            Ok(EventData::Message(Message::from_array_buffer(buffer)))
+        } else if data.is_instance_of::<Blob>() {
+            Ok(EventData::Blob(data.unchecked_into()))
+        } else {
+            Err(WsError::UnexpectedMessageType)
+        }
+    }
+
+    pub(crate) fn from_array_buffer(buffer: &ArrayBuffer) -> Self {
+        Message::Binary(Uint8Array::new(buffer).to_vec())
+    }
+
+    /// A JS string, or bytes from a `Uint8Array` or an `ArrayBuffer`
+    pub fn from_js(value: &JsValue) -> Result<Self, WsError> {
+        if let Some(text) = value.as_string() {
+            Ok(Message::Text(text))
+        } else if let Some(buffer) = value.dyn_ref::<ArrayBuffer>() {
+            Ok(Message::from_array_buffer(buffer))
+        } else if let Some(bytes) = value.dyn_ref::<Uint8Array>() {
+            Ok(Message::Binary(bytes.to_vec()))
+        } else {
+            let msg = "message must be a string, a Uint8Array or an ArrayBuffer";
+            Err(WsError::InvalidArgument(msg.to_string()))
+        }
+    }
+
+    pub(crate) fn send(&self, ws: &WebSocket) -> Result<(), JsValue> {
+        match self {
+            Message::Text(text) => ws.send_with_str(text),
+            Message::Binary(data) => ws.send_with_u8_array(data),
+        }
+    }
+}
+
+/// Text as a JS string, binary as a `Uint8Array`
+impl From<Message> for JsValue {
+    fn from(msg: Message) -> Self {
+        match msg {
+            Message::Text(text) => JsString::from(text).into(),
+            Message::Binary(data) => Uint8Array::from(&data[..]).into(),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Function, Promise};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use super::cancel;
+use super::stream::WsStream;
+use crate::reconnect::{self, Handle};
+use crate::{Message, ReconnectEvent, ReconnectOptions, WsError};
+
+#[derive(Default)]
+struct Hooks {
+    onreconnecting: RefCell<Option<Function>>,
+    onreconnected: RefCell<Option<Function>>,
+}
+
+impl Hooks {
+    /// Call the JS hook of `event` with `{ attempt, delay_ms }`
+    fn emit(&self, event: ReconnectEvent) {
+        let (hook, attempt, delay_ms) = match event {
+            ReconnectEvent::Reconnecting { attempt, delay_ms } => {
+                (&self.onreconnecting, attempt, Some(delay_ms))
+            }
+            ReconnectEvent::Reconnected { attempt } => (&self.onreconnected, attempt, None),
+        };
+        let Some(hook) = hook.borrow().clone() else {
+            return;
+        };
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"attempt".into(), &attempt.into());
+        if let Some(delay_ms) = delay_ms {
+            let _ = js_sys::Reflect::set(&event, &"delay_ms".into(), &delay_ms.into());
+        }
+        if let Err(err) = hook.call1(&JsValue::NULL, &event) {
+            console_err!("event hook threw: {:?}", err);
+        }
+    }
+}
+
+/// A WebSocket client that reconnects when the connection is lost, with exponential backoff and jitter
+///
+/// It connects in the background: messages sent while disconnected are buffered and sent in order once reconnected.
+/// Received messages are read with `next_message` across reconnections
+#[wasm_bindgen]
+pub struct ReconnectingWsClient {
+    handle: Rc<Handle>,
+    hooks: Rc<Hooks>,
+}
+
+#[wasm_bindgen]
+impl ReconnectingWsClient {
+    #[wasm_bindgen(constructor)]
+    pub fn new(endpoint: String, options: Option<ReconnectOptions>) -> Self {
+        let hooks = Rc::new(Hooks::default());
+        let (handle, driver) = reconnect::reconnecting(
+            endpoint,
+            options.unwrap_or_default(),
+            |url| async move { WsStream::connect(&url).await },
+            cancel::sleep,
+            js_sys::Math::random,
+            {
+                let hooks = hooks.clone();
+                move |event| hooks.emit(event)
+            },
+        );
+        wasm_bindgen_futures::spawn_local(driver);
+        ReconnectingWsClient {
+            handle: Rc::new(handle),
+            hooks,
+        }
+    }
+
+    /// Sent now if connected, otherwise once reconnected
+    pub fn send_text(&self, text: &str) -> Result<(), WsError> {
+        self.handle.send(Message::from(text))
+    }
+
+    pub fn send_binary(&self, data: &[u8]) -> Result<(), WsError> {
+        self.handle.send(Message::from(data.to_vec()))
+    }
+
+    /// Resolves with the next message received, in order, whichever connection it came from.
+    /// Rejects once the client is closed or gave up reconnecting, and all messages are read
+    pub fn next_message(&self) -> Promise {
+        let handle = self.handle.clone();
+        future_to_promise(async move {
+            handle
+                .recv()
+                .await
+                .map(JsValue::from)
+                .map_err(JsValue::from)
+        })
+    }
+
+    /// Stop reconnecting and close the connection, with an optional close code (1000 or 3000-4999) and reason
+    pub fn close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        self.handle.close(code, reason)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_connected(&self) -> bool {
+        self.handle.is_connected()
+    }
+
+    /// Messages waiting for the connection
+    #[wasm_bindgen(getter)]
+    pub fn buffered_messages(&self) -> usize {
+        self.handle.buffered_messages()
+    }
+
+    /// Called with `{ attempt, delay_ms }` before waiting to reconnect
+    #[wasm_bindgen(setter)]
+    pub fn set_onreconnecting(&self, hook: Option<Function>) {
+        *self.hooks.onreconnecting.borrow_mut() = hook;
+    }
+
+    /// Called with `{ attempt }` once reconnected
+    #[wasm_bindgen(setter)]
+    pub fn set_onreconnected(&self, hook: Option<Function>) {
+        *self.hooks.onreconnected.borrow_mut() = hook;
+    }
+}
+
+impl ReconnectingWsClient {
+    pub fn send(&self, msg: Message) -> Result<(), WsError> {
+        self.handle.send(msg)
+    }
+
+    /// The next message received, whichever connection it came from
+    pub async fn recv(&self) -> Result<Message, WsError> {
+        self.handle.recv().await
+    }
+}
+
+impl Drop for ReconnectingWsClient {
+    /// Pending `next_message` calls still hold the handle: they complete once closed
+    fn drop(&mut self) {
+        let _ = self.close(None, None);
+    }
+}
@@ -0,0 +1,49 @@
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+use super::error::js_message;
+use crate::request::{Matcher, Predicate};
+use crate::{Message, WsError};
+
+#[wasm_bindgen]
+extern "C" {
+    /// `"echo"`, `{ jsonId: "id" }`, or a predicate called with each reply
+    #[wasm_bindgen(
+        typescript_type = "\"echo\" | { jsonId: string } | ((reply: string | Uint8Array) => boolean)"
+    )]
+    pub type JsMatcher;
+}
+
+/// A JS function, accepting replies it returns a truthy value for
+pub(crate) struct JsPredicate(Function);
+
+impl Predicate for JsPredicate {
+    fn accepts(&mut self, reply: &Message) -> Result<bool, WsError> {
+        match self.0.call1(&JsValue::NULL, &reply.clone().into()) {
+            Ok(accepted) => Ok(accepted.is_truthy()),
+            Err(err) => {
+                let msg = format!("matcher threw: {}", js_message(err));
+                Err(WsError::InvalidArgument(msg))
+            }
+        }
+    }
+}
+
+impl Matcher<JsPredicate> {
+    pub(crate) fn from_js(matcher: &JsValue) -> Result<Self, WsError> {
+        if let Some(predicate) = matcher.dyn_ref::<Function>() {
+            return Ok(Matcher::Predicate(JsPredicate(predicate.clone())));
+        }
+        if matcher.as_string().as_deref() == Some("echo") {
+            return Ok(Matcher::Echo);
+        }
+        if matcher.is_object() {
+            let field = js_sys::Reflect::get(matcher, &"jsonId".into()).ok();
+            if let Some(field) = field.and_then(|field| field.as_string()) {
+                return Ok(Matcher::JsonId(field));
+            }
+        }
+        let msg = "matcher must be \"echo\", { jsonId: string } or a function";
+        Err(WsError::InvalidArgument(msg.to_string()))
+    }
+}
@@ -0,0 +1,298 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use futures::future::poll_fn;
+use futures::{Sink, Stream};
+use js_sys::ArrayBuffer;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+use super::error::js_message;
+use super::message::EventData;
+use crate::protocol::Connection;
+use crate::{Message, WsError};
+
+/// What the socket callbacks share with the stream
+#[derive(Default)]
+struct State {
+    /// In the order received, `None` while a `Blob` is still being read
+    messages: VecDeque<Option<Result<Message, WsError>>>,
+    /// Sequence number of the front of `messages`
+    first_seq: u64,
+    /// Code and reason of the close event
+    closed: Option<(u16, String)>,
+    /// `Err(Closed)` was yielded: the stream is over
+    ended: bool,
+    errored: bool,
+    /// Woken on messages and close
+    reader: Option<Waker>,
+    /// Woken on open, error and close
+    writer: Option<Waker>,
+}
+impl State {
+    /// Reserve the next position, for a message to `fill` in
+    fn reserve(&mut self) -> u64 {
+        self.messages.push_back(None);
+        self.first_seq + self.messages.len() as u64 - 1
+    }
+
+    fn fill(&mut self, seq: u64, msg: Result<Message, WsError>) {
+        if let Some(slot) = seq
+            .checked_sub(self.first_seq)
+            .and_then(|i| self.messages.get_mut(i as usize))
+        {
+            *slot = Some(msg);
+        }
+        self.wake_reader();
+    }
+
+    fn push(&mut self, msg: Result<Message, WsError>) {
+        let seq = self.reserve();
+        self.fill(seq, msg);
+    }
+
+    fn wake_reader(&mut self) {
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+    }
+
+    fn wake_writer(&mut self) {
+        if let Some(waker) = self.writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A browser `WebSocket` as an async [`Stream`] of received messages and a [`Sink`] of messages to send
+///
+/// The stream yields the messages in order, then `Err(WsError::Closed)` with the close code and reason, then ends.
+/// Dropping it closes the socket and detaches its callbacks
+pub struct WsStream {
+    ws: WebSocket,
+    state: Rc<RefCell<State>>,
+    _onopen: Closure<dyn FnMut()>,
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+    _onerror: Closure<dyn FnMut(Event)>,
+    _onclose: Closure<dyn FnMut(CloseEvent)>,
+}
+
+impl WsStream {
+    /// Open a connection to `endpoint`, returns once the socket is open
+    pub async fn connect(endpoint: &str) -> Result<Self, WsError> {
+        console_log!("Connecting to {}", endpoint);
+        let ws = WebSocket::new(endpoint).map_err(|err| WsError::InvalidUrl {
+            url: endpoint.to_string(),
+            message: js_message(err),
+        })?;
+        ws.set_binary_type(BinaryType::Arraybuffer);
+        let state = Rc::new(RefCell::new(State::default()));
+
+        let onopen = {
+            let state = state.clone();
+            Closure::<dyn FnMut()>::new(move || state.borrow_mut().wake_writer())
+        };
+        let onmessage = {
+            let state = state.clone();
+            Closure::<dyn FnMut(_)>::new(move |evt: MessageEvent| {
+                match Message::from_event_data(evt.data()) {
+                    Ok(EventData::Message(msg)) => state.borrow_mut().push(Ok(msg)),
+                    // keep the order of messages while the blob is read
+                    Ok(EventData::Blob(blob)) => {
+                        let seq = state.borrow_mut().reserve();
+                        let state = state.clone();
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let msg = match JsFuture::from(blob.array_buffer()).await {
+                                Ok(buffer) => Ok(Message::from_array_buffer(
+                                    &buffer.unchecked_into::<ArrayBuffer>(),
+                                )),
+                                Err(err) => Err(WsError::from_js(err)),
+                            };
+                            state.borrow_mut().fill(seq, msg)
+                        });
+                    }
+                    Err(err) => state.borrow_mut().push(Err(err)),
+                }
+            })
+        };
+        let onerror = {
+            let state = state.clone();
+            Closure::<dyn FnMut(_)>::new(move |ev: Event| {
+                console_err!("error event: {:?}", ev);
+                let mut state = state.borrow_mut();
+                state.errored = true;
+                state.wake_writer();
+            })
+        };
+        let onclose = {
+            let state = state.clone();
+            Closure::<dyn FnMut(_)>::new(move |evt: CloseEvent| {
+                console_log!("socket closed: {} {:?}", evt.code(), evt.reason());
+                let mut state = state.borrow_mut();
+                state.closed = Some((evt.code(), evt.reason()));
+                state.wake_reader();
+                state.wake_writer();
+            })
+        };
+        ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+        ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+        ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+        let stream = WsStream {
+            ws,
+            state,
+            _onopen: onopen,
+            _onmessage: onmessage,
+            _onerror: onerror,
+            _onclose: onclose,
+        };
+
+        poll_fn(|cx| stream.poll_open(cx))
+            .await
+            .map_err(|_| WsError::ConnectFailed {
+                url: endpoint.to_string(),
+            })?;
+        console_log!("socket opened");
+        Ok(stream)
+    }
+
+    fn poll_open(&self, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        match self.ws.ready_state() {
+            WebSocket::OPEN => Poll::Ready(Ok(())),
+            WebSocket::CONNECTING if !self.state.borrow().errored => {
+                self.state.borrow_mut().writer = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            _ => Poll::Ready(Err(self.closed_error())),
+        }
+    }
+
+    /// `WsError::Closed` with the code and reason of the close event, once there was one
+    pub(crate) fn closed_error(&self) -> WsError {
+        self.close_watcher().error()
+    }
+
+    pub(crate) fn close_watcher(&self) -> CloseWatcher {
+        CloseWatcher(self.state.clone())
+    }
+
+    /// The underlying browser socket, e.g. to close it with a code or read its state
+    pub fn websocket(&self) -> &WebSocket {
+        &self.ws
+    }
+}
+
+impl Connection for WsStream {
+    fn start_close(&self, code: Option<u16>, reason: Option<String>) -> Result<(), WsError> {
+        close(&self.ws, code, reason)
+    }
+}
+
+/// Start the closing handshake of `ws`, with an optional close code (1000 or 3000-4999) and reason
+pub(crate) fn close(
+    ws: &WebSocket,
+    code: Option<u16>,
+    reason: Option<String>,
+) -> Result<(), WsError> {
+    match (code, reason) {
+        (Some(code), Some(reason)) => ws.close_with_code_and_reason(code, &reason),
+        (Some(code), None) => ws.close_with_code(code),
+        (None, _) => ws.close(),
+    }
+    .map_err(|err| WsError::InvalidArgument(js_message(err)))
+}
+
+/// Reads how a stream was closed, without borrowing the stream
+#[derive(Clone)]
+pub(crate) struct CloseWatcher(Rc<RefCell<State>>);
+impl CloseWatcher {
+    pub(crate) fn error(&self) -> WsError {
+        match &self.0.borrow().closed {
+            Some((code, reason)) => WsError::Closed {
+                code: *code,
+                reason: reason.clone(),
+            },
+            // closing, or a failed connection
+            None => WsError::Closed {
+                code: 1006,
+                reason: String::new(),
+            },
+        }
+    }
+}
+
+impl Stream for WsStream {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut state = self.state.borrow_mut();
+        if let Some(Some(_)) = state.messages.front() {
+            state.first_seq += 1;
+            return Poll::Ready(state.messages.pop_front().flatten());
+        }
+        if state.ended {
+            return Poll::Ready(None);
+        }
+        if let (true, Some((code, reason))) = (state.messages.is_empty(), &state.closed) {
+            let err = WsError::Closed {
+                code: *code,
+                reason: reason.clone(),
+            };
+            state.ended = true;
+            return Poll::Ready(Some(Err(err)));
+        }
+        state.reader = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Sink<Message> for WsStream {
+    type Error = WsError;
+
+    /// Waits for the socket to be open
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        self.poll_open(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: Message) -> Result<(), WsError> {
+        msg.send(&self.ws).map_err(WsError::from_js)
+    }
+
+    /// The browser buffers the messages sent: nothing to wait for
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Starts the closing handshake, and waits for the close event
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        let mut state = self.state.borrow_mut();
+        if state.closed.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        if self.ws.ready_state() == WebSocket::CONNECTING
+            || self.ws.ready_state() == WebSocket::OPEN
+        {
+            self.ws.close().map_err(WsError::from_js)?;
+        }
+        state.writer = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for WsStream {
+    fn drop(&mut self) {
+        self.ws.set_onopen(None);
+        self.ws.set_onmessage(None);
+        self.ws.set_onerror(None);
+        self.ws.set_onclose(None);
+        if self.ws.ready_state() == WebSocket::CONNECTING
+            || self.ws.ready_state() == WebSocket::OPEN
+        {
+            let _ = self.ws.close();
+        }
+    }
+}
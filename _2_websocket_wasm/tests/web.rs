@@ -1,23 +1,331 @@
 //! Test suite for the Web and headless browsers.
 #![cfg(target_arch = "wasm32")]
 
-use _2_websocket_wasm::ws_ping2;
+use _2_websocket_wasm::{
+    ws_error_classes, ws_ping, ws_request, Message, ReconnectOptions, ReconnectingWsClient,
+    WsClient, WsError, WsStream,
+};
+use futures::{stream, SinkExt, StreamExt};
+use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
 use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
-const ENDPOINT: &str = "wss://echo.websocket.events";
+
+#[wasm_bindgen(inline_js = "
+export function track_sockets() {
+    const sockets = [];
+    const Original = globalThis.WebSocket;
+    globalThis.WebSocket = class extends Original {
+        constructor(...args) {
+            super(...args);
+            sockets.push(this);
+        }
+    };
+    return { sockets, restore: () => { globalThis.WebSocket = Original; } };
+}
+export function instance_of(value, cls) {
+    return value instanceof cls;
+}
+")]
+extern "C" {
+    /// Records every `WebSocket` created until `restore()` is called
+    fn track_sockets() -> JsValue;
+    fn instance_of(value: &JsValue, cls: &JsValue) -> bool;
+}
+/// The local echo server (`cargo run -p _2_echo_server`), unless `ECHO_SERVER_URL` is set at build time
+const ENDPOINT: &str = match option_env!("ECHO_SERVER_URL") {
+    Some(url) => url,
+    None => "ws://127.0.0.1:9001",
+};
+const BANNER: &str = "Welcome to the echo server";
+
+/// `ENDPOINT`, with the echo server settings of `query`
+fn endpoint(query: &str) -> String {
+    format!("{}/?{}", ENDPOINT.trim_end_matches('/'), query)
+}
 
 #[wasm_bindgen_test]
 async fn pass() -> Result<(), Box<dyn std::error::Error>> {
-    let promise = ws_ping2(ENDPOINT, "hello ws").await;
+    let promise = ws_ping(ENDPOINT, "hello ws", None, None).await;
     let output = JsFuture::from(promise).await.unwrap();
 
-    assert_eq!(
-        output,
-        JsValue::from_str("echo.websocket.events sponsored by Lob.com")
-    );
+    assert_eq!(output, JsValue::from_str(BANNER));
 
     Ok(())
 }
+
+#[wasm_bindgen_test]
+async fn requests_skip_the_banner() {
+    let request = |message: &str, matcher: JsValue| {
+        let promise = ws_request(
+            ENDPOINT,
+            message.into(),
+            matcher.unchecked_into(),
+            None,
+            None,
+        );
+        async { JsFuture::from(promise.await).await }
+    };
+    let output = request("hello ws", "echo".into()).await.unwrap();
+    assert_eq!(output, JsValue::from_str("hello ws"));
+
+    let json = r#"{"id": 1, "method": "ping"}"#;
+    let matcher = js_sys::Object::new();
+    js_sys::Reflect::set(&matcher, &"jsonId".into(), &"id".into()).unwrap();
+    let output = request(json, matcher.into()).await.unwrap();
+    assert_eq!(output, JsValue::from_str(json));
+
+    let predicate = Closure::<dyn FnMut(JsValue) -> bool>::new(|reply: JsValue| {
+        reply
+            .as_string()
+            .is_some_and(|reply| reply.starts_with("hello"))
+    });
+    let output = (request("hello ws", predicate.as_ref().clone()).await).unwrap();
+    assert_eq!(output, JsValue::from_str("hello ws"));
+
+    for invalid in [JsValue::from("first"), js_sys::Object::new().into()] {
+        let err = js_sys::Error::from(request("hello ws", invalid).await.unwrap_err());
+        assert_eq!(err.name(), "InvalidArgumentError");
+    }
+    let throws = js_sys::Function::new_no_args("throw new Error('nope')");
+    let err = js_sys::Error::from(request("hello ws", throws.into()).await.unwrap_err());
+    assert_eq!(err.name(), "InvalidArgumentError");
+}
+
+#[wasm_bindgen_test]
+async fn client_keeps_the_connection_open() {
+    let client = WsClient::connect(ENDPOINT.to_string()).await.unwrap();
+    assert!(client.is_open());
+    // greeting banner
+    JsFuture::from(client.next_message()).await.unwrap();
+
+    for msg in ["first", "second"] {
+        client.send_text(msg).unwrap();
+        let output = JsFuture::from(client.next_message()).await.unwrap();
+        assert_eq!(output, JsValue::from_str(msg));
+    }
+
+    client.close(Some(1000), Some("done".to_string())).unwrap();
+    assert!(JsFuture::from(client.next_message()).await.is_err());
+    assert_eq!(client.ready_state(), web_sys::WebSocket::CLOSED);
+}
+
+#[wasm_bindgen_test]
+async fn binary_messages_roundtrip() {
+    let client = WsClient::connect(ENDPOINT.to_string()).await.unwrap();
+    // greeting banner
+    client.recv().await.unwrap();
+
+    client.send_binary(&[1, 2, 3]).unwrap();
+    assert_eq!(client.recv().await.unwrap(), Message::Binary(vec![1, 2, 3]));
+
+    // blobs are read in order with the following messages
+    client.set_binary_type("blob").unwrap();
+    client.send(&Message::Binary(vec![4, 5])).unwrap();
+    client.send(&Message::from("after")).unwrap();
+    assert_eq!(client.recv().await.unwrap(), Message::Binary(vec![4, 5]));
+    assert_eq!(client.recv().await.unwrap(), Message::from("after"));
+
+    // Uint8Array to JS
+    client.send_binary(&[6]).unwrap();
+    let output = JsFuture::from(client.next_message()).await.unwrap();
+    assert_eq!(js_sys::Uint8Array::from(output).to_vec(), vec![6]);
+    assert!(client.set_binary_type("text").is_err());
+}
+
+#[wasm_bindgen_test]
+async fn ping_timeout_and_abort() {
+    // the server waits longer than that to reply
+    let promise = ws_ping(
+        &endpoint("banner=&delay_ms=1000"),
+        "hello ws",
+        Some(50),
+        None,
+    )
+    .await;
+    let err = js_sys::Error::from(JsFuture::from(promise).await.unwrap_err());
+    assert_eq!(err.name(), "TimeoutError");
+
+    let controller = web_sys::AbortController::new().unwrap();
+    let promise = ws_ping(
+        ENDPOINT,
+        "hello ws",
+        Some(10_000),
+        Some(controller.signal()),
+    )
+    .await;
+    controller.abort();
+    let err = js_sys::Error::from(JsFuture::from(promise).await.unwrap_err());
+    assert_eq!(err.name(), "AbortError");
+
+    // already aborted
+    let promise = ws_ping(ENDPOINT, "hello ws", None, Some(controller.signal())).await;
+    let err = js_sys::Error::from(JsFuture::from(promise).await.unwrap_err());
+    assert_eq!(err.name(), "AbortError");
+}
+
+#[wasm_bindgen_test]
+async fn pings_leave_no_open_socket() {
+    let tracker = track_sockets();
+    for i in 0..20 {
+        let timeout_ms = if i % 4 == 3 { Some(0) } else { None };
+        let promise = ws_ping(ENDPOINT, "hello ws", timeout_ms, None).await;
+        let _ = JsFuture::from(promise).await;
+    }
+    let restore: js_sys::Function = js_sys::Reflect::get(&tracker, &"restore".into())
+        .unwrap()
+        .into();
+    restore.call0(&JsValue::NULL).unwrap();
+
+    let sockets: js_sys::Array = js_sys::Reflect::get(&tracker, &"sockets".into())
+        .unwrap()
+        .into();
+    assert_eq!(sockets.length(), 20);
+    // every socket was closed (the closing handshakes themselves complete asynchronously)
+    for ws in sockets.iter().map(web_sys::WebSocket::from) {
+        assert!(ws.ready_state() >= web_sys::WebSocket::CLOSING);
+    }
+}
+
+#[wasm_bindgen_test]
+async fn stream_and_sink() {
+    let mut ws = WsStream::connect(ENDPOINT).await.unwrap();
+    // greeting banner
+    ws.next().await.unwrap().unwrap();
+
+    let mut messages = stream::iter(["a", "b", "c"].map(|msg| Ok(Message::from(msg))));
+    ws.send_all(&mut messages).await.unwrap();
+    let mut replies = Vec::new();
+    while let Some(msg) = ws.next().await {
+        replies.push(msg.unwrap());
+        if replies.len() == 3 {
+            break;
+        }
+    }
+    assert_eq!(replies, ["a", "b", "c"].map(Message::from));
+
+    ws.websocket()
+        .close_with_code_and_reason(1000, "done")
+        .unwrap();
+    let closed = WsError::Closed {
+        code: 1000,
+        reason: "done".to_string(),
+    };
+    assert_eq!(ws.next().await, Some(Err(closed)));
+    assert_eq!(ws.next().await, None);
+}
+
+#[wasm_bindgen_test]
+async fn typed_errors() {
+    let classes = ws_error_classes();
+    let class = |name: &str| js_sys::Reflect::get(&classes, &name.into()).unwrap();
+    let field = |err: &JsValue, name: &str| js_sys::Reflect::get(err, &name.into()).unwrap();
+
+    let promise = ws_ping("not a url", "hello ws", None, None).await;
+    let err = JsFuture::from(promise).await.unwrap_err();
+    assert!(instance_of(&err, &class("InvalidUrlError")));
+    assert!(instance_of(&err, &class("WsError")));
+    assert!(err.is_instance_of::<js_sys::Error>());
+    assert_eq!(field(&err, "code"), "INVALID_URL");
+
+    let client = WsClient::connect(ENDPOINT.to_string()).await.unwrap();
+    assert!(matches!(
+        client.close(Some(1), None),
+        Err(WsError::InvalidArgument(_))
+    ));
+    client.close(Some(4000), Some("bye".to_string())).unwrap();
+    let err = loop {
+        if let Err(err) = client.recv().await {
+            break err;
+        }
+    };
+    let closed = WsError::Closed {
+        code: 4000,
+        reason: "bye".to_string(),
+    };
+    assert_eq!(err, closed);
+    assert!(matches!(
+        client.send_text("late"),
+        Err(WsError::Closed { .. })
+    ));
+
+    let err = JsFuture::from(client.next_message()).await.unwrap_err();
+    assert!(instance_of(&err, &class("ClosedError")));
+    assert_eq!(field(&err, "name"), "ClosedError");
+    assert_eq!(field(&err, "code"), "CLOSED");
+    assert_eq!(field(&err, "closeCode"), 4000);
+    assert_eq!(field(&err, "reason"), "bye");
+}
+
+#[wasm_bindgen_test]
+async fn reconnecting_client_buffers_until_connected() {
+    let client = ReconnectingWsClient::new(ENDPOINT.to_string(), None);
+    client.send_text("buffered").unwrap();
+    assert_eq!(client.buffered_messages(), 1);
+
+    // greeting banner
+    client.recv().await.unwrap();
+    assert_eq!(client.recv().await.unwrap(), Message::from("buffered"));
+    assert!(client.is_connected());
+    assert_eq!(client.buffered_messages(), 0);
+
+    client.close(Some(1000), None).unwrap();
+    assert!(matches!(
+        client.recv().await,
+        Err(WsError::Closed { code: 1000, .. })
+    ));
+    assert!(client.send_text("late").is_err());
+}
+
+#[wasm_bindgen_test]
+async fn reconnecting_client_gives_up_after_max_attempts() {
+    let attempts = js_sys::Array::new();
+    let onreconnecting = {
+        let attempts = attempts.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |event| {
+            attempts.push(&js_sys::Reflect::get(&event, &"attempt".into()).unwrap());
+        })
+    };
+    let options = ReconnectOptions {
+        initial_delay_ms: 10,
+        max_attempts: Some(2),
+        ..ReconnectOptions::default()
+    };
+    // nothing listens there
+    let client = ReconnectingWsClient::new("ws://127.0.0.1:1".to_string(), Some(options));
+    client.set_onreconnecting(Some(onreconnecting.as_ref().clone().unchecked_into()));
+
+    assert!(matches!(
+        client.recv().await,
+        Err(WsError::ConnectFailed { .. })
+    ));
+    assert_eq!(attempts.to_vec(), [JsValue::from(1), JsValue::from(2)]);
+}
+
+#[wasm_bindgen_test]
+async fn reconnecting_client_reconnects_when_closed_by_the_server() {
+    let options = ReconnectOptions {
+        initial_delay_ms: 10,
+        jitter: 0.0,
+        ..ReconnectOptions::default()
+    };
+    // each connection is closed after one echo
+    let url = endpoint("banner=&close_after=1&close_code=4000");
+    let client = ReconnectingWsClient::new(url, Some(options));
+    let mut onreconnected = None;
+    let reconnected = js_sys::Promise::new(&mut |resolve, _| onreconnected = Some(resolve));
+    client.set_onreconnected(onreconnected);
+
+    client.send_text("first").unwrap();
+    assert_eq!(client.recv().await.unwrap(), Message::from("first"));
+    let event = JsFuture::from(reconnected).await.unwrap();
+    assert_eq!(
+        js_sys::Reflect::get(&event, &"attempt".into()).unwrap(),
+        JsValue::from(1)
+    );
+    client.send_text("second").unwrap();
+    assert_eq!(client.recv().await.unwrap(), Message::from("second"));
+    client.close(None, None).unwrap();
+}
@@ -17,9 +17,11 @@
           inherit system;
           overlays = [ (import rust-overlay) ];
         };
-        customRust = pkgs.rust-bin.stable."1.80.0".default.override {
+        # 1.85: the criterion benchmarks of _1_zk_proof depend on clap 4.6, which requires Rust 1.85
+        customRust = pkgs.rust-bin.stable."1.85.0".default.override {
           extensions = [ "rust-src" "rust-analyzer" ];
-          targets = [ "wasm32-unknown-unknown" ];
+          # thumbv7em-none-eabihf has no std: it checks the no_std build
+          targets = [ "wasm32-unknown-unknown" "thumbv7em-none-eabihf" ];
         };
         binaries = my-utils.binaries.${system} // {
           geckodriver = "${pkgs.geckodriver}/bin/geckodriver";
@@ -27,7 +29,8 @@
 
         baseInputs = with pkgs; [
           customRust
-          python3
+          (python3.withPackages (ps: [ ps.ecdsa ps.pytest ]))
+          maturin
           wasm-pack
           nodejs_18
           # bun
@@ -57,15 +60,30 @@
 
           (writeScriptBin "test1" ''cargo nextest run --package _1_zk_proof --nocapture -- $SINGLE_TEST '')
 
+          (writeScriptBin "test1-py" ''set -euxo pipefail
+            cd _1_zk_proof/python/
+            python -m venv --system-site-packages .venv
+            VIRTUAL_ENV=$PWD/.venv maturin develop
+            .venv/bin/python -m pytest tests/
+          '')
+          (writeScriptBin "test1-wasm" ''wasm-pack test --node _1_zk_proof/wasm '')
+          (writeScriptBin "build1-wasm" ''wasm-pack build _1_zk_proof/wasm --target web --out-dir .cache/zk-proof-wasm-web '')
+          (writeScriptBin "build1-nostd" ''cargo build --package _1_zk_proof --lib --no-default-features --target thumbv7em-none-eabihf '')
+
           (writeScriptBin "test2" ''set -euxo pipefail
+            cargo build --package _2_echo_server
+            ./target/debug/echo_server & trap "kill $!" EXIT
             cd _2_websocket_wasm/
             wasm-pack test --firefox --headless --geckodriver ${binaries.geckodriver} --
           '')
+          (writeScriptBin "run2-echo" ''cargo run --package _2_echo_server -- "$@" '')
           (writeScriptBin "build2-deno" ''
             wasm-pack build _2_websocket_wasm --target deno --out-dir .cache/my-wasm-deno
           '')
           (writeScriptBin "run2" ''
             wasm-pack build _2_websocket_wasm --target web --out-dir .cache/my-wasm-web
+            cargo build --package _2_echo_server
+            ./target/debug/echo_server & trap "kill $!" EXIT
             cd _2_websocket_wasm/my_vite_web_app; rm -rf node_modules/my-wasm-web;
             pnpm i; pnpm start
           '')